@@ -1,24 +1,33 @@
-use ::core::cell::RefCell;
 use raylib::prelude::*;
-use std::rc::Rc;
 
 use crate::colors::*;
 
-pub fn binomial(n: u64, k: u64) -> u64 {
-    if n >= 63 {
-        panic!("N is too great, will overflow!");
-    }
-    if k > n {
-        0
-    } else if k == 0 {
-        1
-    } else {
-        n * binomial(n - 1, n - k) / k
-    }
-}
+mod arc_length;
+pub use arc_length::ArcLengthTable;
+mod bspline;
+pub use bspline::BSpline;
+mod catmull_rom;
+pub use catmull_rom::CatmullRomSpline;
+mod flatten;
+pub use flatten::bezier_flatten;
+mod intersection;
+pub use intersection::{
+    bezier_intersections, bezier_line_intersections, bezier_self_intersections, Intersection, Line,
+    LineIntersection, LineKind,
+};
+mod nearest;
+pub use nearest::{nearest_point_on_bezier, NearestPoint};
+mod offset;
+pub use offset::{bezier_stroke_outline, StrokeCap, StrokeJoin, StrokeStyle};
+mod rational;
+pub use rational::rational_circle_arc;
+use rational::{evaluate_rational_bezier, homogeneous_levels, is_rational, rational_derivatives};
+mod spline;
+pub use spline::{JoinMode, Spline, SplineJoin, SplinePointId, SplinePointKind};
 
-const POINTS_RADIUS: f32 = 10.0;
-const POINTS_RADIUS_HOVER: f32 = 15.0;
+/// Radius of the points in screen pixels (divided by the zoom of the camera in world space)
+pub const POINTS_RADIUS: f32 = 10.0;
+pub const POINTS_RADIUS_HOVER: f32 = 15.0;
 const ANIMATION_SPEED: f32 = 1.0;
 #[derive(Debug, Clone, Copy)]
 pub struct BasicPoint {
@@ -27,6 +36,8 @@ pub struct BasicPoint {
     pub color: Color,
     pub is_selected: bool,
     pub is_hovered: bool,
+    /// Weight of the point in a rational curve
+    pub weight: f32,
 }
 impl BasicPoint {
     pub fn new(position: Vector2, color: Color) -> Self {
@@ -36,6 +47,7 @@ impl BasicPoint {
             color,
             is_selected: false,
             is_hovered: false,
+            weight: 1.0,
         }
     }
 }
@@ -43,6 +55,9 @@ impl Point for BasicPoint {
     fn get_position(&self) -> Vector2 {
         self.position
     }
+    fn get_weight(&self) -> f32 {
+        self.weight
+    }
 }
 impl MovablePoint for BasicPoint {
     fn set_position(&mut self, position: Vector2, _with_constraint: bool) {
@@ -71,241 +86,39 @@ impl PointGui for BasicPoint {
     fn set_selected(&mut self, state: bool) {
         self.is_selected = state;
     }
-}
-
-#[derive(Clone)]
-pub struct JoinPoint {
-    position: Vector2,
-    radius: f32,
-    is_selected: bool,
-    is_hovered: bool,
-
-    previous_control_point: Option<Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-    next_control_point: Option<Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-}
-impl JoinPoint {
-    pub fn new(
-        position: Vector2,
-        previous_control_point: Option<&Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-        next_control_point: Option<&Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-    ) -> Self {
-        Self {
-            position,
-            radius: POINTS_RADIUS,
-            is_selected: false,
-            is_hovered: false,
-            previous_control_point: previous_control_point.cloned(),
-            next_control_point: next_control_point.cloned(),
-        }
-    }
-}
-impl Point for JoinPoint {
-    fn get_position(&self) -> Vector2 {
-        self.position
-    }
-}
-impl MovablePoint for JoinPoint {
-    fn set_position(&mut self, position: Vector2, with_constraint: bool) {
-        let movement_diff = self.position - position;
-        self.position = position;
-
-        if with_constraint {
-            if let Some(previous_control_point) = &self.previous_control_point {
-                let previous_position = previous_control_point.borrow().get_position();
-                previous_control_point
-                    .borrow_mut()
-                    .set_position(previous_position - movement_diff, false);
-            }
-            if let Some(next_control_point) = &self.next_control_point {
-                let next_position = next_control_point.borrow().get_position();
-                next_control_point
-                    .borrow_mut()
-                    .set_position(next_position - movement_diff, false);
-            }
-        }
-    }
-}
-impl PointGui for JoinPoint {
-    fn get_radius(&self) -> f32 {
-        self.radius
-    }
-    fn set_radius(&mut self, radius: f32) {
-        self.radius = radius;
-    }
-    fn get_color(&self) -> Color {
-        COLOR_BLUE
-    }
-    fn is_hovered(&self) -> bool {
-        self.is_hovered
-    }
-    fn set_hover_state(&mut self, state: bool) {
-        self.is_hovered = state;
-    }
-    fn is_selected(&self) -> bool {
-        self.is_selected
-    }
-    fn set_selected(&mut self, state: bool) {
-        self.is_selected = state;
-    }
-}
-pub enum JoinPointConstraintID {
-    PreviousControlPoint = 0,
-    NextControlPoint = 1,
-}
-impl TryFrom<usize> for JoinPointConstraintID {
-    type Error = ();
-
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(Self::PreviousControlPoint),
-            1 => Ok(Self::NextControlPoint),
-            _ => Err(()),
-        }
-    }
-}
-impl MovableGuiPoint for JoinPoint {
-    fn downcast_basic_point(&self) -> BasicPoint {
-        let mut basic_point = BasicPoint::new(self.position, self.get_color());
-        basic_point.radius = self.radius;
-        basic_point.is_selected = self.is_selected;
-        basic_point.is_hovered = self.is_hovered;
-        basic_point
-    }
-
-    fn set_constraint(
-        &mut self,
-        constraint_id: usize,
-        constraint: &Rc<RefCell<Box<dyn MovableGuiPoint>>>,
-    ) {
-        match constraint_id.try_into() {
-            Ok(JoinPointConstraintID::PreviousControlPoint) => {
-                self.previous_control_point = Some(constraint.clone())
-            }
-            Ok(JoinPointConstraintID::NextControlPoint) => {
-                self.next_control_point = Some(constraint.clone())
-            }
-            Err(_) => {}
-        };
+    /// Heavier points are drawn bigger
+    fn draw(&self, d: &mut impl RaylibDraw, zoom: f32) {
+        d.draw_circle_v(
+            self.position,
+            self.radius * self.weight.sqrt().clamp(0.5, 2.0) / zoom,
+            self.color,
+        );
     }
 }
 
-#[derive(Clone)]
-pub struct ControlPoint {
-    position: Vector2,
-    radius: f32,
-    is_selected: bool,
-    is_hovered: bool,
-
-    linked_control_point: Option<Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-    mirror_join_point: Option<Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-}
-impl ControlPoint {
-    pub fn new(
-        position: Vector2,
-        linked_point: Option<&Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-        mirror_join_point: Option<&Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
-    ) -> Self {
-        Self {
-            position,
-            radius: POINTS_RADIUS,
-            is_selected: false,
-            is_hovered: false,
-            linked_control_point: linked_point.cloned(),
-            mirror_join_point: mirror_join_point.cloned(),
-        }
+pub trait Point {
+    fn get_position(&self) -> Vector2;
+    /// Weight of the point in a rational curve (curves with equal weights are plain Bezier curves)
+    fn get_weight(&self) -> f32 {
+        1.0
     }
 }
-impl Point for ControlPoint {
+impl Point for Vector2 {
     fn get_position(&self) -> Vector2 {
-        self.position
+        *self
     }
 }
-impl MovablePoint for ControlPoint {
-    fn set_position(&mut self, position: Vector2, with_constraint: bool) {
-        self.position = position;
-
-        if with_constraint {
-            if let Some(linked_point) = &self.linked_control_point {
-                let join_position = self
-                    .mirror_join_point
-                    .as_ref()
-                    .unwrap()
-                    .borrow()
-                    .get_position();
-                linked_point
-                    .borrow_mut()
-                    .set_position(join_position * 2.0 - position, false);
-            }
-        }
-    }
-}
-impl PointGui for ControlPoint {
-    fn get_radius(&self) -> f32 {
-        self.radius
-    }
-    fn set_radius(&mut self, radius: f32) {
-        self.radius = radius;
-    }
-    fn get_color(&self) -> Color {
-        COLOR_LIGHT
-    }
-    fn is_hovered(&self) -> bool {
-        self.is_hovered
-    }
-    fn set_hover_state(&mut self, state: bool) {
-        self.is_hovered = state;
-    }
-    fn is_selected(&self) -> bool {
-        self.is_selected
-    }
-    fn set_selected(&mut self, state: bool) {
-        self.is_selected = state;
-    }
-}
-pub enum ControlPointConstraintID {
-    LinkedControlPoint = 0,
-    MirrorJoinPoint = 1,
-}
-impl TryFrom<usize> for ControlPointConstraintID {
-    type Error = ();
-
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(Self::LinkedControlPoint),
-            1 => Ok(Self::MirrorJoinPoint),
-            _ => Err(()),
-        }
+/// Position with its weight
+pub type WeightedPoint = (Vector2, f32);
+impl Point for WeightedPoint {
+    fn get_position(&self) -> Vector2 {
+        self.0
     }
-}
-impl MovableGuiPoint for ControlPoint {
-    fn downcast_basic_point(&self) -> BasicPoint {
-        let mut basic_point = BasicPoint::new(self.position, self.get_color());
-        basic_point.radius = self.radius;
-        basic_point.is_selected = self.is_selected;
-        basic_point.is_hovered = self.is_hovered;
-        basic_point
-    }
-    fn set_constraint(
-        &mut self,
-        constraint_id: usize,
-        constraint: &Rc<RefCell<Box<dyn MovableGuiPoint>>>,
-    ) {
-        match constraint_id.try_into() {
-            Ok(ControlPointConstraintID::LinkedControlPoint) => {
-                self.linked_control_point = Some(constraint.clone())
-            }
-            Ok(ControlPointConstraintID::MirrorJoinPoint) => {
-                self.mirror_join_point = Some(constraint.clone())
-            }
-            Err(_) => {}
-        };
+    fn get_weight(&self) -> f32 {
+        self.1
     }
 }
 
-pub trait Point {
-    fn get_position(&self) -> Vector2;
-}
-
 pub trait PointGui: Point {
     fn get_radius(&self) -> f32;
     fn set_radius(&mut self, radius: f32);
@@ -316,12 +129,15 @@ pub trait PointGui: Point {
     fn is_selected(&self) -> bool;
     fn set_selected(&mut self, state: bool);
 
+    /// Whether the position is inside the point
+    /// The radius is in screen pixels so that points keep their size whatever the zoom of the camera.
+    fn is_under(&self, position: Vector2, zoom: f32) -> bool {
+        position.distance_to(self.get_position()) * zoom < self.get_radius()
+    }
+
     /// Update the point (default implementation)
-    fn udpate_gui(&mut self, mouse_position: Vector2) {
-        self.set_hover_state(
-            mouse_position.distance_to(self.get_position()) < self.get_radius()
-                || self.is_selected(),
-        );
+    fn udpate_gui(&mut self, mouse_position: Vector2, zoom: f32) {
+        self.set_hover_state(self.is_under(mouse_position, zoom) || self.is_selected());
         if self.is_hovered() {
             if self.get_radius() < POINTS_RADIUS_HOVER {
                 self.set_radius(self.get_radius() + ANIMATION_SPEED);
@@ -336,8 +152,12 @@ pub trait PointGui: Point {
     }
 
     /// Draw the point (default implementation)
-    fn draw(&self, d: &mut RaylibDrawHandle) {
-        d.draw_circle_v(self.get_position(), self.get_radius(), self.get_color());
+    fn draw(&self, d: &mut impl RaylibDraw, zoom: f32) {
+        d.draw_circle_v(
+            self.get_position(),
+            self.get_radius() / zoom,
+            self.get_color(),
+        );
     }
 }
 
@@ -345,154 +165,910 @@ pub trait MovablePoint {
     fn set_position(&mut self, position: Vector2, with_constraint: bool);
 }
 
-pub trait MovableGuiPoint: MovablePoint + PointGui {
-    fn downcast_basic_point(&self) -> BasicPoint;
-    fn set_constraint(
-        &mut self,
-        constraint_id: usize,
-        constraint: &Rc<RefCell<Box<dyn MovableGuiPoint>>>,
-    );
-}
-
-const SAMPLES: usize = 50;
+pub const SAMPLES: usize = 50;
 
 /// Evaluate a point on the curve
+/// Uses De Casteljau's algorithm which stays numerically stable for any number of points.
+/// Rational curves are evaluated in homogeneous coordinates.
 pub fn evalute_bezier_curve(points: &[impl Point], t: f32) -> Vector2 {
-    let n = points.len() - 1;
-    let tuple_point = points.iter().enumerate().fold((0.0, 0.0), |acc, (i, e)| {
-        let a = (binomial(n as u64, i as u64) as f32)
-            * (1.0 - t).powi((n - i) as i32)
-            * t.powi(i as i32);
-        (
-            acc.0 + e.get_position().x * a,
-            acc.1 + e.get_position().y * a,
-        )
-    });
-    Vector2::new(tuple_point.0, tuple_point.1)
-}
-
-/// Draw the curve
-pub fn draw_bezier(points: &[impl PointGui], d: &mut RaylibDrawHandle, t: Option<f32>) {
+    if is_rational(points) {
+        return evaluate_rational_bezier(points, t);
+    }
+    let mut lerp_points = points.iter().map(|p| p.get_position()).collect::<Vec<_>>();
+    let n = lerp_points.len();
+    for level in 1..n {
+        for i in 0..n - level {
+            lerp_points[i] = lerp_points[i].lerp(lerp_points[i + 1], t);
+        }
+    }
+    lerp_points[0]
+}
+
+/// Every intermediate level of De Casteljau's algorithm, from the control points down to the point on the curve
+pub fn de_casteljau_levels(points: &[impl Point], t: f32) -> Vec<Vec<Vector2>> {
+    homogeneous_levels(points, t)
+        .iter()
+        .map(|level| level.iter().map(|p| p.position()).collect())
+        .collect()
+}
+
+/// Split the curve at t, returning the control points of both sub-curves with their weights
+pub fn rational_bezier_split_at(
+    points: &[impl Point],
+    t: f32,
+) -> (Vec<WeightedPoint>, Vec<WeightedPoint>) {
+    let levels = homogeneous_levels(points, t);
+    let left = levels
+        .iter()
+        .map(|level| (level[0].position(), level[0].weight))
+        .collect();
+    let right = levels
+        .iter()
+        .rev()
+        .map(|level| {
+            let point = level[level.len() - 1];
+            (point.position(), point.weight)
+        })
+        .collect();
+    (left, right)
+}
+
+/// Split a polynomial curve at t, returning the control points of both sub-curves
+/// Rational curves need `rational_bezier_split_at` to keep their weights.
+pub fn bezier_split_at(points: &[Vector2], t: f32) -> (Vec<Vector2>, Vec<Vector2>) {
+    let (left, right) = rational_bezier_split_at(points, t);
+    (
+        left.into_iter().map(|(p, _)| p).collect(),
+        right.into_iter().map(|(p, _)| p).collect(),
+    )
+}
+
+/// Control points of the part of the curve between t0 and t1, with their weights
+/// When t0 is after t1 the part goes backward, from t0 to t1.
+pub fn rational_bezier_sub_curve(points: &[impl Point], t0: f32, t1: f32) -> Vec<WeightedPoint> {
+    if t0 > t1 {
+        let mut sub_curve = rational_bezier_sub_curve(points, t1, t0);
+        sub_curve.reverse();
+        return sub_curve;
+    }
+    let (left, _) = rational_bezier_split_at(points, t1);
+    if t1.abs() < f32::EPSILON {
+        return left;
+    }
+    let (_, sub_curve) = rational_bezier_split_at(&left, t0 / t1);
+    sub_curve
+}
+
+/// Control points of the derivative curve (hodograph) of a polynomial curve
+/// The derivative of a rational curve is not a Bezier curve, see `evalute_bezier_derivative`.
+pub fn bezier_derivative_points(points: &[Vector2]) -> Vec<Vector2> {
+    let n = points.len() as f32 - 1.0;
+    points.windows(2).map(|w| (w[1] - w[0]) * n).collect()
+}
+
+fn positions(points: &[impl Point]) -> Vec<Vector2> {
+    points.iter().map(|p| p.get_position()).collect()
+}
+
+/// Evaluate the first derivative of the curve
+pub fn evalute_bezier_derivative(points: &[impl Point], t: f32) -> Vector2 {
+    if points.len() < 2 {
+        return Vector2::zero();
+    }
+    if is_rational(points) {
+        return rational_derivatives(points, t).0;
+    }
+    evalute_bezier_curve(&bezier_derivative_points(&positions(points)), t)
+}
+
+/// Evaluate the second derivative of the curve
+pub fn evalute_bezier_second_derivative(points: &[impl Point], t: f32) -> Vector2 {
+    if points.len() < 3 {
+        return Vector2::zero();
+    }
+    if is_rational(points) {
+        return rational_derivatives(points, t).1;
+    }
+    evalute_bezier_curve(
+        &bezier_derivative_points(&bezier_derivative_points(&positions(points))),
+        t,
+    )
+}
+
+/// Unit tangent of the curve
+/// When the derivative vanishes (coincident control points) the direction of the curve around t is used instead.
+pub fn bezier_tangent(points: &[impl Point], t: f32) -> Vector2 {
+    const EPSILON: f32 = 1e-4;
+    let derivative = evalute_bezier_derivative(points, t);
+    if derivative.length() > EPSILON {
+        return derivative.normalized();
+    }
+    let chord = evalute_bezier_curve(points, (t + EPSILON).min(1.0))
+        - evalute_bezier_curve(points, (t - EPSILON).max(0.0));
+    if chord.length() > 0.0 {
+        chord.normalized()
+    } else {
+        Vector2::zero()
+    }
+}
+
+/// Unit normal of the curve (tangent rotated by +90°)
+pub fn bezier_normal(points: &[impl Point], t: f32) -> Vector2 {
+    let tangent = bezier_tangent(points, t);
+    Vector2::new(-tangent.y, tangent.x)
+}
+
+/// Signed curvature of the curve, positive when the curve turns toward its normal
+pub fn bezier_curvature(points: &[impl Point], t: f32) -> f32 {
+    let d1 = evalute_bezier_derivative(points, t);
+    let d2 = evalute_bezier_second_derivative(points, t);
+    let speed = d1.length();
+    if speed < 1e-6 {
+        return 0.0;
+    }
+    (d1.x * d2.y - d1.y * d2.x) / (speed * speed * speed)
+}
+
+/// Maximum distance between a drawn curve and the actual one in screen pixels (divided by the zoom of the camera in
+/// world space)
+pub const DRAW_TOLERANCE: f32 = 0.1;
+
+/// Draw only the curve itself (no control points), within the tolerance of the actual curve
+pub fn draw_bezier_line(
+    points: &[impl Point],
+    d: &mut impl RaylibDraw,
+    thick: f32,
+    color: Color,
+    tolerance: f32,
+) {
+    for line_points in bezier_flatten(points, tolerance).windows(2) {
+        d.draw_line_ex(
+            line_points[0].position,
+            line_points[1].position,
+            thick,
+            color,
+        );
+    }
+}
+
+/// Draw the curve (the points and the construction of t keep their screen size whatever the zoom)
+pub fn draw_bezier(points: &[impl PointGui], d: &mut impl RaylibDraw, t: Option<f32>, zoom: f32) {
     for line_points in points.windows(2) {
         d.draw_line_ex(
             line_points[0].get_position(),
             line_points[1].get_position(),
-            3.0,
+            3.0 / zoom,
             COLOR_RED,
         );
     }
 
     let mut final_point = None;
     if let Some(t) = t {
-        let rec_size = Vector2::new(POINTS_RADIUS, POINTS_RADIUS);
-        let mut debug_points: Vec<Vector2> =
-            points.iter().map(|p| p.get_position()).collect::<Vec<_>>();
-        while debug_points.len() > 2 {
-            let next_points = debug_points
-                .windows(2)
-                .map(|w| w[0].lerp(w[1], t))
-                .collect::<Vec<_>>();
+        let rec_size = Vector2::new(POINTS_RADIUS, POINTS_RADIUS) * (1.0 / zoom);
+        let levels = de_casteljau_levels(points, t);
+        for next_points in levels.iter().skip(1).filter(|level| level.len() > 1) {
             // Drawing lines before points so that points will override them
             for p in next_points.windows(2) {
-                d.draw_line_ex(p[0], p[1], 2.0, COLOR_RED);
+                d.draw_line_ex(p[0], p[1], 2.0 / zoom, COLOR_RED);
             }
             // Draw lerp points for this run
             for p in next_points.iter() {
                 d.draw_rectangle_v(*p - rec_size * 0.5, rec_size, COLOR_GREEN);
             }
-            debug_points = next_points;
         }
-        final_point = Some(debug_points[0].lerp(debug_points[1], t));
+        final_point = levels.last().map(|level| level[0]);
     }
 
-    let step = 1.0 / SAMPLES as f32;
-    let step_points = (0..=SAMPLES)
-        .map(|i| evalute_bezier_curve(points, i as f32 * step))
-        .collect::<Vec<_>>();
-
-    for line_points in step_points.windows(2) {
-        d.draw_line_ex(line_points[0], line_points[1], 3.0, COLOR_GREEN);
-    }
+    draw_bezier_line(points, d, 3.0 / zoom, COLOR_GREEN, DRAW_TOLERANCE / zoom);
 
     if let Some(final_point) = final_point {
-        d.draw_circle_v(final_point, POINTS_RADIUS / 2.0, COLOR_YELLOW);
+        d.draw_circle_v(final_point, POINTS_RADIUS / 2.0 / zoom, COLOR_YELLOW);
     }
 
     for point in points.iter() {
-        point.draw(d);
+        point.draw(d, zoom);
     }
 }
 
-pub fn cubic_bezier_bounding_box(points: &[impl Point]) -> Result<Rectangle, String> {
-    if points.len() != 4 {
-        return Err("Cubic Bezier curve needs 4 points".to_string());
-    }
-    let p0 = points[0].get_position();
-    let p1 = points[1].get_position();
-    let p2 = points[2].get_position();
-    let p3 = points[3].get_position();
+/// Least squares and reparametrization passes of the fit
+/// The parameters converge slowly on strongly curved samples: with fewer passes, merging two halves of a segment
+/// leaves its handles pixels away from the original ones.
+const FIT_ITERATIONS: usize = 12;
 
-    let a = -p0 * 3.0 + p1 * 9.0 - p2 * 9.0 + p3 * 3.0;
-    let b = p0 * 6.0 - p1 * 12.0 + p2 * 6.0;
-    let c = -p0 * 3.0 + p1 * 3.0;
+/// Fit a single cubic Bezier through a polyline (least squares, Schneider's method)
+/// Both end points are kept and the handles follow the given tangent directions (in the direction of travel).
+pub fn fit_cubic_bezier(
+    samples: &[Vector2],
+    start_tangent: Vector2,
+    end_tangent: Vector2,
+) -> [Vector2; 4] {
+    let start = samples[0];
+    let end = samples[samples.len() - 1];
+    let chord = start.distance_to(end);
+    let start_tangent = start_tangent.normalized();
+    let end_tangent = end_tangent.normalized();
+    let fallback = [
+        start,
+        start + start_tangent * chord / 3.0,
+        end - end_tangent * chord / 3.0,
+        end,
+    ];
+
+    // Chord length parametrization of the samples
+    let mut params = vec![0.0; samples.len()];
+    for i in 1..samples.len() {
+        params[i] = params[i - 1] + samples[i].distance_to(samples[i - 1]);
+    }
+    let total_length = params[params.len() - 1];
+    if total_length < f32::EPSILON {
+        return fallback;
+    }
+    params.iter_mut().for_each(|u| *u /= total_length);
 
-    let mut bbs = vec![p0, p3]; // Possible bounding box points
+    let mut fitted = fallback;
+    for _ in 0..FIT_ITERATIONS {
+        let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (sample, u) in samples.iter().zip(params.iter()) {
+            let v = 1.0 - u;
+            let (b0, b1, b2, b3) = (v * v * v, 3.0 * u * v * v, 3.0 * u * u * v, u * u * u);
+            let a0 = start_tangent * b1;
+            let a1 = -end_tangent * b2;
+            let residual = *sample - start * (b0 + b1) - end * (b2 + b3);
+            c00 += a0.dot(a0);
+            c01 += a0.dot(a1);
+            c11 += a1.dot(a1);
+            x0 += a0.dot(residual);
+            x1 += a1.dot(residual);
+        }
+        let determinant = c00 * c11 - c01 * c01;
+        if determinant.abs() < f32::EPSILON {
+            return fallback;
+        }
+        let alpha = (x0 * c11 - x1 * c01) / determinant;
+        let beta = (c00 * x1 - c01 * x0) / determinant;
+        if alpha < chord * 1e-3 || beta < chord * 1e-3 {
+            return fallback;
+        }
+        fitted = [
+            start,
+            start + start_tangent * alpha,
+            end - end_tangent * beta,
+            end,
+        ];
 
-    if a.x == 0.0 {
-        if b.x != 0.0 {
-            let t = -c.x / b.x;
-            bbs.push(evalute_bezier_curve(points, t));
+        // Newton-Raphson step to move each parameter to the closest point of the fitted curve
+        for (sample, u) in samples.iter().zip(params.iter_mut()) {
+            let difference = evalute_bezier_curve(&fitted, *u) - *sample;
+            let d1 = evalute_bezier_derivative(&fitted, *u);
+            let d2 = evalute_bezier_second_derivative(&fitted, *u);
+            let denominator = d1.dot(d1) + difference.dot(d2);
+            if denominator.abs() > f32::EPSILON {
+                *u = (*u - difference.dot(d1) / denominator).clamp(0.0, 1.0);
+            }
         }
+    }
+    fitted
+}
+
+/// Raise the degree of the curve by one without changing its shape
+pub fn bezier_elevate_degree(points: &[impl Point]) -> Vec<Vector2> {
+    let positions = points.iter().map(|p| p.get_position()).collect::<Vec<_>>();
+    let n = positions.len();
+    if n == 0 {
+        return positions;
+    }
+    let mut elevated = Vec::with_capacity(n + 1);
+    elevated.push(positions[0]);
+    for i in 1..n {
+        let a = i as f32 / n as f32;
+        elevated.push(positions[i - 1] * a + positions[i] * (1.0 - a));
+    }
+    elevated.push(positions[n - 1]);
+    elevated
+}
+
+const CUBIC_APPROXIMATION_SAMPLES: usize = 32;
+const CUBIC_APPROXIMATION_MAX_DEPTH: u32 = 8;
+
+/// Distance from a point to a polyline
+fn distance_to_polyline(position: Vector2, polyline: &[Vector2]) -> f32 {
+    polyline
+        .windows(2)
+        .map(|w| {
+            let segment = w[1] - w[0];
+            let length_sqr = segment.dot(segment);
+            let t = if length_sqr > 0.0 {
+                ((position - w[0]).dot(segment) / length_sqr).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            position.distance_to(w[0] + segment * t)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn approximate_with_cubics(
+    points: &[WeightedPoint],
+    tolerance: f32,
+    depth: u32,
+    cubics: &mut Vec<[Vector2; 4]>,
+) {
+    let step = 1.0 / CUBIC_APPROXIMATION_SAMPLES as f32;
+    let samples = (0..=CUBIC_APPROXIMATION_SAMPLES)
+        .map(|i| evalute_bezier_curve(points, i as f32 * step))
+        .collect::<Vec<_>>();
+    let fitted = fit_cubic_bezier(
+        &samples,
+        bezier_tangent(points, 0.0),
+        bezier_tangent(points, 1.0),
+    );
+    // Much finer than the tolerance so that the polyline error stays negligible
+    let fitted_samples = bezier_flatten(&fitted, tolerance * 0.1)
+        .into_iter()
+        .map(|p| p.position)
+        .collect::<Vec<_>>();
+    let error = samples
+        .iter()
+        .map(|sample| distance_to_polyline(*sample, &fitted_samples))
+        .fold(0.0, f32::max);
+
+    if error <= tolerance || depth == 0 {
+        cubics.push(fitted);
+    } else {
+        let (left, right) = rational_bezier_split_at(points, 0.5);
+        approximate_with_cubics(&left, tolerance, depth - 1, cubics);
+        approximate_with_cubics(&right, tolerance, depth - 1, cubics);
+    }
+}
+
+/// Convert a curve of any degree into a chain of cubic Bezier
+/// Curves up to degree 3 are converted exactly by degree elevation. Higher degrees and rational curves are split
+/// until each fitted cubic stays within the tolerance of the curve.
+pub fn bezier_to_cubics(points: &[impl Point], tolerance: f32) -> Vec<[Vector2; 4]> {
+    let mut positions = points.iter().map(|p| p.get_position()).collect::<Vec<_>>();
+    if positions.len() < 2 {
+        return Vec::new();
+    }
+    let weighted_points = if is_rational(points) {
+        points
+            .iter()
+            .map(|p| (p.get_position(), p.get_weight()))
+            .collect::<Vec<_>>()
     } else {
-        let x_delta = b.x * b.x - 4.0 * a.x * c.x;
-        if x_delta >= 0.0 {
-            let x_t_min = (-b.x - x_delta.sqrt()) / (2.0 * a.x);
-            let x_t_max = (-b.x + x_delta.sqrt()) / (2.0 * a.x);
-            bbs.push(evalute_bezier_curve(points, x_t_min.clamp(0.0, 1.0)));
-            bbs.push(evalute_bezier_curve(points, x_t_max.clamp(0.0, 1.0)));
+        while positions.len() < 4 {
+            positions = bezier_elevate_degree(&positions);
+        }
+        if positions.len() == 4 {
+            return vec![[positions[0], positions[1], positions[2], positions[3]]];
         }
+        positions.into_iter().map(|p| (p, 1.0)).collect()
+    };
+    let mut cubics = Vec::new();
+    approximate_with_cubics(
+        &weighted_points,
+        tolerance,
+        CUBIC_APPROXIMATION_MAX_DEPTH,
+        &mut cubics,
+    );
+    cubics
+}
+
+const FRAME_LENGTH: f32 = 50.0;
+
+/// Draw the tangent & normal vectors at a point of a curve (they keep their screen size whatever the zoom)
+pub fn draw_curve_frame(
+    d: &mut impl RaylibDraw,
+    position: Vector2,
+    tangent: Vector2,
+    normal: Vector2,
+    zoom: f32,
+) {
+    let length = FRAME_LENGTH / zoom;
+    d.draw_line_ex(
+        position,
+        position + tangent * length,
+        2.0 / zoom,
+        COLOR_BLUE,
+    );
+    d.draw_line_ex(
+        position,
+        position + normal * length,
+        2.0 / zoom,
+        COLOR_YELLOW,
+    );
+}
+
+/// Draw the curvature value next to a point of a curve (screen space)
+pub fn draw_curvature_label(d: &mut impl RaylibDraw, screen_position: Vector2, curvature: f32) {
+    d.draw_text(
+        format!("k = {:.4}", curvature).as_str(),
+        (screen_position.x + POINTS_RADIUS) as i32,
+        (screen_position.y + POINTS_RADIUS) as i32,
+        18,
+        COLOR_LIGHT,
+    );
+}
+
+const ROOT_TOLERANCE: f32 = 1e-6;
+const ROOT_MAX_DEPTH: u32 = 32;
+
+/// Roots strictly inside ]t0, t1[ (the end points are checked by the caller)
+fn bezier_roots_in(values: &[f32], t0: f32, t1: f32, depth: u32, roots: &mut Vec<f32>) {
+    // Variation diminishing property: no sign change in the coefficients means no root
+    let has_sign_change = values.iter().any(|v| *v < 0.0) && values.iter().any(|v| *v > 0.0);
+    if !has_sign_change {
+        return;
+    }
+    if depth == 0 || t1 - t0 < ROOT_TOLERANCE {
+        roots.push((t0 + t1) * 0.5);
+        return;
+    }
+    // Split the 1D curve in half with De Casteljau's algorithm
+    let mut left = Vec::with_capacity(values.len());
+    let mut right = Vec::with_capacity(values.len());
+    let mut level = values.to_vec();
+    while !level.is_empty() {
+        left.push(level[0]);
+        right.push(level[level.len() - 1]);
+        level = level.windows(2).map(|w| (w[0] + w[1]) * 0.5).collect();
+    }
+    right.reverse();
+    let middle = (t0 + t1) * 0.5;
+    bezier_roots_in(&left, t0, middle, depth - 1, roots);
+    // The middle is an end point of both halves
+    if right[0] == 0.0 {
+        roots.push(middle);
     }
+    bezier_roots_in(&right, middle, t1, depth - 1, roots);
+}
 
-    if a.y == 0.0 {
-        if b.y != 0.0 {
-            let t = -c.y / b.y;
-            bbs.push(evalute_bezier_curve(points, t));
+/// Roots in [0, 1] of a 1D Bezier curve given by its control values (recursive subdivision)
+/// Roots touching zero without crossing it are not reported, except at the end points.
+pub fn bezier_roots(values: &[f32]) -> Vec<f32> {
+    let mut roots = Vec::new();
+    if values.iter().any(|v| v.abs() > f32::EPSILON) {
+        if values[0] == 0.0 {
+            roots.push(0.0);
         }
-    } else {
-        let y_delta = b.y * b.y - 4.0 * a.y * c.y;
-        if y_delta >= 0.0 {
-            let y_t_min = (-b.y - y_delta.sqrt()) / (2.0 * a.y);
-            let y_t_max = (-b.y + y_delta.sqrt()) / (2.0 * a.y);
-            bbs.push(evalute_bezier_curve(points, y_t_min.clamp(0.0, 1.0)));
-            bbs.push(evalute_bezier_curve(points, y_t_max.clamp(0.0, 1.0)));
+        bezier_roots_in(values, 0.0, 1.0, ROOT_MAX_DEPTH, &mut roots);
+        if values[values.len() - 1] == 0.0 {
+            roots.push(1.0);
         }
     }
+    // Roots lying on a subdivision boundary are found by both halves
+    roots.dedup_by(|a, b| (*a - *b).abs() < ROOT_TOLERANCE * 2.0);
+    roots
+}
 
-    let x_min: f32 = bbs
-        .iter()
-        .map(|bb| bb.x)
-        .min_by(|a, b| a.total_cmp(b))
-        .unwrap();
-    let x_max: f32 = bbs
-        .iter()
-        .map(|bb| bb.x)
-        .max_by(|a, b| a.total_cmp(b))
-        .unwrap();
-    let y_min: f32 = bbs
-        .iter()
-        .map(|bb| bb.y)
-        .min_by(|a, b| a.total_cmp(b))
-        .unwrap();
-    let y_max: f32 = bbs
+/// Natural logarithms of the factorials up to n
+fn ln_factorials(n: usize) -> Vec<f64> {
+    let mut ln_factorials = vec![0.0; n + 1];
+    for i in 1..=n {
+        ln_factorials[i] = ln_factorials[i - 1] + (i as f64).ln();
+    }
+    ln_factorials
+}
+
+/// Parameters where the derivative of a 1D curve (rational with the given weights) changes sign
+/// The derivative of a rational curve N / W has the sign of N'W - NW', a polynomial of degree 2n - 1 whose Bernstein
+/// coefficients come from the products of the Bernstein polynomials of N' and W.
+pub fn bezier_derivative_roots(values: &[f32], weights: &[f32]) -> Vec<f32> {
+    if values.len() < 2 {
+        return Vec::new();
+    }
+    if weights.iter().all(|w| *w == 1.0) {
+        return bezier_roots(&values.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>());
+    }
+    let n = values.len() - 1;
+    let numerator = values
         .iter()
-        .map(|bb| bb.y)
-        .max_by(|a, b| a.total_cmp(b))
-        .unwrap();
+        .zip(weights.iter())
+        .map(|(v, w)| v * w)
+        .collect::<Vec<_>>();
+    let ln_factorials = ln_factorials(2 * n - 1);
+    let ln_binomial =
+        |n: usize, k: usize| ln_factorials[n] - ln_factorials[k] - ln_factorials[n - k];
+    let mut coefficients = vec![0.0; 2 * n];
+    for i in 0..n {
+        for j in 0..=n {
+            // B(i, n - 1) * B(j, n) = C(n - 1, i) C(n, j) / C(2n - 1, i + j) * B(i + j, 2n - 1)
+            let product =
+                (ln_binomial(n - 1, i) + ln_binomial(n, j) - ln_binomial(2 * n - 1, i + j)).exp();
+            let term = (numerator[i + 1] - numerator[i]) * weights[j]
+                - (weights[i + 1] - weights[i]) * numerator[j];
+            coefficients[i + j] += (product * term as f64) as f32;
+        }
+    }
+    bezier_roots(&coefficients)
+}
+
+/// Tight bounding box of a curve of any degree
+/// Extremums are found at the end points and at the roots of the derivative on each axis.
+pub fn bezier_bounding_box(points: &[impl Point]) -> Result<Rectangle, String> {
+    if points.is_empty() {
+        return Err("Bezier curve needs at least 1 point".to_string());
+    }
+    let mut bbs = vec![
+        points[0].get_position(),
+        points[points.len() - 1].get_position(),
+    ]; // Possible bounding box points
+    if points.len() > 2 {
+        let weights = points.iter().map(|p| p.get_weight()).collect::<Vec<_>>();
+        let axis_roots = |axis: fn(Vector2) -> f32| {
+            let values = points
+                .iter()
+                .map(|p| axis(p.get_position()))
+                .collect::<Vec<_>>();
+            bezier_derivative_roots(&values, &weights)
+        };
+        bbs.extend(
+            axis_roots(|p| p.x)
+                .into_iter()
+                .chain(axis_roots(|p| p.y))
+                .map(|t| evalute_bezier_curve(points, t)),
+        );
+    }
+
+    let x_min = bbs.iter().map(|bb| bb.x).fold(f32::MAX, f32::min);
+    let x_max = bbs.iter().map(|bb| bb.x).fold(f32::MIN, f32::max);
+    let y_min = bbs.iter().map(|bb| bb.y).fold(f32::MAX, f32::min);
+    let y_max = bbs.iter().map(|bb| bb.y).fold(f32::MIN, f32::max);
 
     Ok(Rectangle {
         x: x_min,
         y: y_min,
-        width: (x_min - x_max).abs(),
-        height: (y_min - y_max).abs(),
+        width: x_max - x_min,
+        height: y_max - y_min,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Previous Bernstein polynomial implementation, kept as a reference for low degrees.
+    fn binomial(n: u64, k: u64) -> u64 {
+        if k > n {
+            0
+        } else {
+            (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+        }
+    }
+
+    fn evalute_bezier_curve_bernstein(points: &[impl Point], t: f32) -> Vector2 {
+        let n = points.len() - 1;
+        let tuple_point = points.iter().enumerate().fold((0.0, 0.0), |acc, (i, e)| {
+            let a = (binomial(n as u64, i as u64) as f32)
+                * (1.0 - t).powi((n - i) as i32)
+                * t.powi(i as i32);
+            (
+                acc.0 + e.get_position().x * a,
+                acc.1 + e.get_position().y * a,
+            )
+        });
+        Vector2::new(tuple_point.0, tuple_point.1)
+    }
+
+    fn make_points(positions: &[(f32, f32)]) -> Vec<BasicPoint> {
+        positions
+            .iter()
+            .map(|(x, y)| BasicPoint::new(Vector2::new(*x, *y), COLOR_LIGHT))
+            .collect()
+    }
+
+    fn assert_close(a: Vector2, b: Vector2, epsilon: f32) {
+        assert!(
+            a.distance_to(b) < epsilon,
+            "{:?} and {:?} differ by more than {}",
+            a,
+            b,
+            epsilon
+        );
+    }
+
+    #[test]
+    fn de_casteljau_matches_bernstein_at_low_degrees() {
+        let positions = [
+            (300.0, 600.0),
+            (600.0, 300.0),
+            (900.0, 300.0),
+            (1200.0, 600.0),
+            (1000.0, 900.0),
+            (400.0, 800.0),
+            (100.0, 100.0),
+        ];
+        for n in 2..=positions.len() {
+            let points = make_points(&positions[..n]);
+            for i in 0..=20 {
+                let t = i as f32 / 20.0;
+                assert_close(
+                    evalute_bezier_curve(&points, t),
+                    evalute_bezier_curve_bernstein(&points, t),
+                    1e-2,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn curve_interpolates_end_points() {
+        let points = make_points(&[(0.0, 0.0), (50.0, 100.0), (100.0, 0.0)]);
+        assert_close(
+            evalute_bezier_curve(&points, 0.0),
+            Vector2::new(0.0, 0.0),
+            1e-6,
+        );
+        assert_close(
+            evalute_bezier_curve(&points, 1.0),
+            Vector2::new(100.0, 0.0),
+            1e-6,
+        );
+        assert_close(
+            evalute_bezier_curve(&points, 0.5),
+            Vector2::new(50.0, 50.0),
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn high_degree_curve_is_stable() {
+        let points = (0..200)
+            .map(|i| {
+                let angle = i as f32 * 0.1;
+                BasicPoint::new(
+                    Vector2::new(500.0 + angle.cos() * 300.0, 500.0 + angle.sin() * 300.0),
+                    COLOR_LIGHT,
+                )
+            })
+            .collect::<Vec<_>>();
+        for i in 0..=50 {
+            let p = evalute_bezier_curve(&points, i as f32 / 50.0);
+            assert!(p.x.is_finite() && p.y.is_finite());
+            // Curve stays inside the convex hull of its control points
+            assert!(p.distance_to(Vector2::new(500.0, 500.0)) <= 300.0 + 1e-2);
+        }
+        assert_close(evalute_bezier_curve(&points, 0.0), points[0].position, 1e-3);
+        assert_close(
+            evalute_bezier_curve(&points, 1.0),
+            points[199].position,
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn derivatives_match_end_point_formulas() {
+        let points = make_points(&[(0.0, 0.0), (10.0, 20.0), (30.0, 20.0), (40.0, 0.0)]);
+        assert_close(
+            evalute_bezier_derivative(&points, 0.0),
+            Vector2::new(30.0, 60.0),
+            1e-4,
+        );
+        assert_close(
+            evalute_bezier_derivative(&points, 1.0),
+            Vector2::new(30.0, -60.0),
+            1e-4,
+        );
+        assert_close(
+            evalute_bezier_second_derivative(&points, 0.0),
+            Vector2::new(60.0, -120.0),
+            1e-4,
+        );
+    }
+
+    #[test]
+    fn curvature_of_parabola() {
+        // y = x^2 for x in [-1, 1]
+        let points = make_points(&[(-1.0, 1.0), (0.0, -1.0), (1.0, 1.0)]);
+        assert!((bezier_curvature(&points, 0.5) - 2.0).abs() < 1e-4);
+        assert_close(bezier_tangent(&points, 0.5), Vector2::new(1.0, 0.0), 1e-6);
+        assert_close(bezier_normal(&points, 0.5), Vector2::new(0.0, 1.0), 1e-6);
+    }
+
+    #[test]
+    fn tangent_with_coincident_control_points() {
+        let points = make_points(&[(0.0, 0.0), (0.0, 0.0), (10.0, 10.0), (20.0, 0.0)]);
+        let tangent = bezier_tangent(&points, 0.0);
+        assert!((tangent.length() - 1.0).abs() < 1e-4);
+        assert!(tangent.x > 0.0 && tangent.y > 0.0);
+    }
+
+    #[test]
+    fn split_halves_reproduce_curve() {
+        let points = make_points(&[
+            (300.0, 600.0),
+            (600.0, 300.0),
+            (900.0, 300.0),
+            (1200.0, 600.0),
+            (700.0, 900.0),
+        ]);
+        for split in [0.0, 0.25, 0.5, 0.8, 1.0] {
+            let (left, right) = bezier_split_at(&positions(&points), split);
+            assert_eq!(left.len(), points.len());
+            assert_eq!(right.len(), points.len());
+            for i in 0..=20 {
+                let u = i as f32 / 20.0;
+                assert_close(
+                    evalute_bezier_curve(&left, u),
+                    evalute_bezier_curve(&points, u * split),
+                    1e-2,
+                );
+                assert_close(
+                    evalute_bezier_curve(&right, u),
+                    evalute_bezier_curve(&points, split + u * (1.0 - split)),
+                    1e-2,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sub_curve_reproduces_curve() {
+        let points = make_points(&[(0.0, 0.0), (100.0, 300.0), (400.0, -200.0), (500.0, 100.0)]);
+        // Reversed bounds give the same part going backward
+        for (t0, t1) in [(0.2, 0.7), (0.7, 0.2)] {
+            let sub_curve = rational_bezier_sub_curve(&points, t0, t1);
+            for i in 0..=20 {
+                let u = i as f32 / 20.0;
+                assert_close(
+                    evalute_bezier_curve(&sub_curve, u),
+                    evalute_bezier_curve(&points, t0 + u * (t1 - t0)),
+                    1e-2,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fit_cubic_reproduces_cubic() {
+        let points = make_points(&[(0.0, 0.0), (100.0, 200.0), (300.0, 200.0), (400.0, 0.0)]);
+        let samples = (0..=50)
+            .map(|i| evalute_bezier_curve(&points, i as f32 / 50.0))
+            .collect::<Vec<_>>();
+        let fitted = fit_cubic_bezier(
+            &samples,
+            bezier_tangent(&points, 0.0),
+            bezier_tangent(&points, 1.0),
+        );
+        for (fitted_point, point) in fitted.iter().zip(points.iter()) {
+            assert_close(*fitted_point, point.position, 1.0);
+        }
+    }
+
+    #[test]
+    fn fit_cubic_merges_split_halves() {
+        // Deleting the join inserted in the middle of a segment must give the segment back
+        let points = make_points(&[(0.0, 0.0), (50.0, 300.0), (450.0, 300.0), (400.0, 0.0)]);
+        let (left, right) = bezier_split_at(&positions(&points), 0.3);
+        let samples = (0..=25)
+            .map(|i| evalute_bezier_curve(&left, i as f32 / 25.0))
+            .chain((1..=25).map(|i| evalute_bezier_curve(&right, i as f32 / 25.0)))
+            .collect::<Vec<_>>();
+        let fitted = fit_cubic_bezier(
+            &samples,
+            bezier_tangent(&points, 0.0),
+            bezier_tangent(&points, 1.0),
+        );
+        for (fitted_point, point) in fitted.iter().zip(points.iter()) {
+            assert_close(*fitted_point, point.position, 1.0);
+        }
+    }
+
+    #[test]
+    fn degree_elevation_keeps_shape() {
+        let points = make_points(&[(0.0, 0.0), (100.0, 200.0), (300.0, -50.0)]);
+        let elevated = bezier_elevate_degree(&points);
+        assert_eq!(elevated.len(), 4);
+        for i in 0..=20 {
+            let t = i as f32 / 20.0;
+            assert_close(
+                evalute_bezier_curve(&points, t),
+                evalute_bezier_curve(&elevated, t),
+                1e-3,
+            );
+        }
+    }
+
+    #[test]
+    fn high_degree_to_cubics() {
+        let points = make_points(&[
+            (0.0, 0.0),
+            (100.0, 300.0),
+            (200.0, -200.0),
+            (300.0, 300.0),
+            (400.0, -100.0),
+            (500.0, 200.0),
+        ]);
+        let cubics = bezier_to_cubics(&points, 0.5);
+        assert!(cubics.len() > 1);
+        assert_close(cubics[0][0], points[0].position, 1e-3);
+        assert_close(cubics[cubics.len() - 1][3], points[5].position, 1e-3);
+        for pair in cubics.windows(2) {
+            assert_close(pair[0][3], pair[1][0], 1e-3);
+        }
+
+        let approximation = cubics
+            .iter()
+            .flat_map(|cubic| (0..=50).map(|i| evalute_bezier_curve(cubic, i as f32 / 50.0)))
+            .collect::<Vec<_>>();
+        for i in 0..=200 {
+            let position = evalute_bezier_curve(&points, i as f32 / 200.0);
+            assert!(distance_to_polyline(position, &approximation) < 1.0);
+        }
+    }
+
+    #[test]
+    fn roots_of_1d_curves() {
+        // (t - 0.25)(t - 0.75) in Bernstein form
+        let roots = bezier_roots(&[0.1875, -0.3125, 0.1875]);
+        assert_eq!(roots.len(), 2);
+        assert!((roots[0] - 0.25).abs() < 1e-5);
+        assert!((roots[1] - 0.75).abs() < 1e-5);
+        assert!(bezier_roots(&[1.0, 0.5, 2.0]).is_empty());
+        assert!(bezier_roots(&[0.0, 0.0]).is_empty());
+        // Roots at the end points are found in both directions
+        assert_eq!(bezier_roots(&[2.0, 1.0, 0.0]), vec![1.0]);
+        assert_eq!(bezier_roots(&[0.0, 1.0, 2.0]), vec![0.0]);
+        assert_eq!(bezier_roots(&[1.0, 0.0, -1.0]), vec![0.5]);
+    }
+
+    #[test]
+    fn bounding_box_of_any_degree() {
+        let cubic = make_points(&[(0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0)]);
+        let bb = bezier_bounding_box(&cubic).unwrap();
+        assert_close(Vector2::new(bb.x, bb.y), Vector2::new(0.0, 0.0), 1e-3);
+        assert!((bb.width - 100.0).abs() < 1e-3);
+        assert!((bb.height - 75.0).abs() < 1e-3);
+
+        let points = make_points(&[
+            (0.0, 0.0),
+            (100.0, 300.0),
+            (200.0, -200.0),
+            (300.0, 300.0),
+            (400.0, -100.0),
+            (500.0, 200.0),
+        ]);
+        let bb = bezier_bounding_box(&points).unwrap();
+        let samples = (0..=1000)
+            .map(|i| evalute_bezier_curve(&points, i as f32 / 1000.0))
+            .collect::<Vec<_>>();
+        let y_min = samples.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let y_max = samples.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((bb.y - y_min).abs() < 1e-2);
+        assert!((bb.y + bb.height - y_max).abs() < 1e-2);
+        assert!(bezier_bounding_box(&[] as &[Vector2]).is_err());
+    }
+
+    #[test]
+    fn bounding_box_of_rational_curves() {
+        let points = [
+            (Vector2::new(0.0, 0.0), 1.0),
+            (Vector2::new(100.0, 300.0), 4.0),
+            (Vector2::new(400.0, -100.0), 0.5),
+            (Vector2::new(300.0, 200.0), 2.0),
+        ];
+        let bb = bezier_bounding_box(&points).unwrap();
+        let samples = (0..=10000)
+            .map(|i| evalute_bezier_curve(&points, i as f32 / 10000.0))
+            .collect::<Vec<_>>();
+        let x_min = samples.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+        let x_max = samples.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        let y_min = samples.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let y_max = samples.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        assert!((bb.x - x_min).abs() < 1e-2);
+        assert!((bb.x + bb.width - x_max).abs() < 1e-2);
+        assert!((bb.y - y_min).abs() < 1e-2);
+        assert!((bb.y + bb.height - y_max).abs() < 1e-2);
+
+        // Third of a circle centered on the origin, from -60° to 60°
+        let arc = rational_circle_arc(Vector2::zero(), 100.0, -PI / 3.0, 2.0 * PI / 3.0);
+        let bb = bezier_bounding_box(&arc).unwrap();
+        assert_close(
+            Vector2::new(bb.x, bb.y),
+            Vector2::new(50.0, -86.602_54),
+            1e-2,
+        );
+        assert!((bb.width - 50.0).abs() < 1e-2);
+        assert!((bb.height - 173.205_08).abs() < 1e-2);
+    }
+}
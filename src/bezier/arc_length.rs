@@ -0,0 +1,229 @@
+use raylib::prelude::*;
+
+use super::{
+    bezier_derivative_points, evalute_bezier_curve, evalute_bezier_derivative, is_rational,
+    positions, Point, WeightedPoint,
+};
+
+/// 5 points Gauss-Legendre abscissas and weights on [-1, 1]
+const GAUSS_LEGENDRE: [(f32, f32); 5] = [
+    (0.0, 0.568_888_9),
+    (-0.538_469_3, 0.478_628_7),
+    (0.538_469_3, 0.478_628_7),
+    (-0.906_179_8, 0.236_926_9),
+    (0.906_179_8, 0.236_926_9),
+];
+const LENGTH_TOLERANCE: f32 = 1e-5;
+const MAX_SUBDIVISION_DEPTH: u32 = 12;
+const LUT_SAMPLES: usize = 32;
+const NEWTON_ITERATIONS: usize = 4;
+
+/// Speed of a curve along t
+enum Speed {
+    /// Control points of the derivative of a polynomial curve
+    Polynomial(Vec<Vector2>),
+    /// Control points of a rational curve with their weights (its derivative is not a Bezier curve)
+    Rational(Vec<WeightedPoint>),
+}
+impl Speed {
+    fn new(points: &[impl Point]) -> Self {
+        if is_rational(points) {
+            Self::Rational(
+                points
+                    .iter()
+                    .map(|p| (p.get_position(), p.get_weight()))
+                    .collect(),
+            )
+        } else if points.len() < 2 {
+            Self::Polynomial(vec![Vector2::zero()])
+        } else {
+            Self::Polynomial(bezier_derivative_points(&positions(points)))
+        }
+    }
+
+    fn at(&self, t: f32) -> f32 {
+        match self {
+            Self::Polynomial(derivative) => evalute_bezier_curve(derivative, t).length(),
+            Self::Rational(points) => evalute_bezier_derivative(points, t).length(),
+        }
+    }
+}
+
+/// Integrate the speed of the curve between t0 and t1
+fn gauss_length(speed: &Speed, t0: f32, t1: f32) -> f32 {
+    let half = (t1 - t0) * 0.5;
+    let middle = (t1 + t0) * 0.5;
+    GAUSS_LEGENDRE
+        .iter()
+        .map(|(x, w)| w * speed.at(middle + half * x))
+        .sum::<f32>()
+        * half
+}
+
+fn adaptive_length(speed: &Speed, t0: f32, t1: f32, whole: f32, depth: u32) -> f32 {
+    let middle = (t0 + t1) * 0.5;
+    let left = gauss_length(speed, t0, middle);
+    let right = gauss_length(speed, middle, t1);
+    if depth == 0 || (left + right - whole).abs() <= LENGTH_TOLERANCE * (left + right) {
+        left + right
+    } else {
+        adaptive_length(speed, t0, middle, left, depth - 1)
+            + adaptive_length(speed, middle, t1, right, depth - 1)
+    }
+}
+
+/// Lookup table mapping distances along a chain of curves to (segment, t) and back
+pub struct ArcLengthTable {
+    speeds: Vec<Speed>,
+    /// Distance from the start of the chain at evenly spaced t values of each segment
+    samples: Vec<Vec<f32>>,
+    length: f32,
+}
+impl ArcLengthTable {
+    pub fn new<P: Point>(segments: &[impl AsRef<[P]>]) -> Self {
+        let mut speeds = Vec::with_capacity(segments.len());
+        let mut samples = Vec::with_capacity(segments.len());
+        let mut length = 0.0;
+        for segment in segments.iter() {
+            let points = segment.as_ref();
+            let speed = Speed::new(points);
+            let mut segment_samples = Vec::with_capacity(LUT_SAMPLES + 1);
+            segment_samples.push(length);
+            for i in 0..LUT_SAMPLES {
+                let t0 = i as f32 / LUT_SAMPLES as f32;
+                let t1 = (i + 1) as f32 / LUT_SAMPLES as f32;
+                let whole = gauss_length(&speed, t0, t1);
+                length += adaptive_length(&speed, t0, t1, whole, MAX_SUBDIVISION_DEPTH);
+                segment_samples.push(length);
+            }
+            speeds.push(speed);
+            samples.push(segment_samples);
+        }
+
+        Self {
+            speeds,
+            samples,
+            length,
+        }
+    }
+
+    pub fn from_curve(points: &[impl Point]) -> Self {
+        Self::new(&[points])
+    }
+
+    /// Total length of the chain
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Distance from the start of the chain to the point at t on the given segment
+    pub fn t_to_distance(&self, segment: usize, t: f32) -> f32 {
+        let Some(segment_samples) = self.samples.get(segment) else {
+            return self.length;
+        };
+        let t = t.clamp(0.0, 1.0);
+        let i = ((t * LUT_SAMPLES as f32) as usize).min(LUT_SAMPLES - 1);
+        segment_samples[i] + gauss_length(&self.speeds[segment], i as f32 / LUT_SAMPLES as f32, t)
+    }
+
+    /// Segment and t of the point at the given distance from the start of the chain
+    pub fn distance_to_t(&self, distance: f32) -> (usize, f32) {
+        if self.samples.is_empty() {
+            return (0, 0.0);
+        }
+        let distance = distance.clamp(0.0, self.length);
+        let segment = self
+            .samples
+            .iter()
+            .rposition(|s| s[0] <= distance)
+            .unwrap_or(0);
+        let segment_samples = &self.samples[segment];
+        let i = segment_samples
+            .partition_point(|d| *d <= distance)
+            .clamp(1, LUT_SAMPLES)
+            - 1;
+        let (d0, d1) = (segment_samples[i], segment_samples[i + 1]);
+        let t_min = i as f32 / LUT_SAMPLES as f32;
+        let t_max = (i + 1) as f32 / LUT_SAMPLES as f32;
+        let mut t = if d1 > d0 {
+            t_min + (distance - d0) / (d1 - d0) * (t_max - t_min)
+        } else {
+            t_min
+        };
+
+        // Refine the linear interpolation with Newton's method
+        for _ in 0..NEWTON_ITERATIONS {
+            let error = self.t_to_distance(segment, t) - distance;
+            let speed = self.speeds[segment].at(t);
+            if speed < 1e-6 || error.abs() < 1e-4 {
+                break;
+            }
+            t = (t - error / speed).clamp(t_min, t_max);
+        }
+        (segment, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn straight_line_length() {
+        let points = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(20.0, 0.0),
+            Vector2::new(30.0, 40.0),
+        ];
+        let line = [Vector2::new(0.0, 0.0), Vector2::new(30.0, 40.0)];
+        assert!((ArcLengthTable::from_curve(&line).length() - 50.0).abs() < 1e-3);
+        assert!(ArcLengthTable::from_curve(&points).length() > 50.0);
+    }
+
+    #[test]
+    fn quarter_circle_length() {
+        // Classic cubic approximation of a quarter of the unit circle scaled to radius 100
+        let k = 0.552_284_8 * 100.0;
+        let points = [
+            Vector2::new(100.0, 0.0),
+            Vector2::new(100.0, k),
+            Vector2::new(k, 100.0),
+            Vector2::new(0.0, 100.0),
+        ];
+        let expected = std::f32::consts::FRAC_PI_2 * 100.0;
+        assert!((ArcLengthTable::from_curve(&points).length() - expected).abs() < 0.05);
+    }
+
+    #[test]
+    fn lookup_table_round_trip() {
+        let segments = vec![
+            [
+                Vector2::new(300.0, 600.0),
+                Vector2::new(600.0, 300.0),
+                Vector2::new(900.0, 300.0),
+                Vector2::new(1200.0, 600.0),
+            ],
+            [
+                Vector2::new(1200.0, 600.0),
+                Vector2::new(1500.0, 900.0),
+                Vector2::new(1200.0, 900.0),
+                Vector2::new(1250.0, 1200.0),
+            ],
+        ];
+        let table = ArcLengthTable::new(&segments);
+        let expected = ArcLengthTable::from_curve(&segments[0]).length()
+            + ArcLengthTable::from_curve(&segments[1]).length();
+        assert!((table.length() - expected).abs() < 1e-2);
+
+        for i in 0..=100 {
+            let distance = table.length() * i as f32 / 100.0;
+            let (segment, t) = table.distance_to_t(distance);
+            assert!((table.t_to_distance(segment, t) - distance).abs() < 1e-2);
+        }
+        assert!(
+            (table.t_to_distance(1, 0.0) - ArcLengthTable::from_curve(&segments[0]).length()).abs()
+                < 1e-2
+        );
+    }
+}
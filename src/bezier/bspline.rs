@@ -0,0 +1,740 @@
+use raylib::prelude::*;
+use std::cell::RefCell;
+
+use super::rational::{homogeneous_points, HomogeneousPoint};
+use super::{BasicPoint, WeightedPoint};
+use crate::colors::*;
+
+/// Shortest domain a knot can be dragged to
+const MIN_DOMAIN_LENGTH: f32 = 1e-3;
+
+/// Evenly spaced knots in [0, 1], with the end knots repeated degree + 1 times when clamped
+pub fn uniform_knots(point_count: usize, degree: usize, is_clamped: bool) -> Vec<f32> {
+    let knot_count = point_count + degree + 1;
+    if is_clamped {
+        let span_count = point_count - degree;
+        (0..knot_count)
+            .map(|i| i.saturating_sub(degree).min(span_count) as f32 / span_count as f32)
+            .collect()
+    } else {
+        (0..knot_count)
+            .map(|i| i as f32 / (knot_count - 1) as f32)
+            .collect()
+    }
+}
+
+/// Bezier decomposition of a B-spline with the control net it was computed from
+#[derive(Debug, Clone)]
+struct BezierCache {
+    points: Vec<WeightedPoint>,
+    knots: Vec<f32>,
+    degree: usize,
+    spans: Vec<((f32, f32), Vec<WeightedPoint>)>,
+}
+
+/// B-spline curve of any degree, rational (NURBS) when the weights of its points differ
+/// The knot vector holds `points + degree + 1` non-decreasing values and the curve is defined between the knots
+/// `degree` and `points`. With clamped knots (end knots repeated degree + 1 times) the curve starts and ends on
+/// the first and last control points.
+#[derive(Debug, Clone)]
+pub struct BSpline {
+    points: Vec<BasicPoint>,
+    knots: Vec<f32>,
+    degree: usize,
+    /// The points can be changed through `points_mut`, so the cache is checked against the control net on each use
+    bezier_cache: RefCell<Option<BezierCache>>,
+}
+impl BSpline {
+    /// Create a B-spline with uniform knots (more points than the degree are needed)
+    pub fn new(positions: &[Vector2], degree: usize, is_clamped: bool) -> Option<Self> {
+        if positions.len() <= degree {
+            return None;
+        }
+        let points = positions
+            .iter()
+            .map(|position| (*position, 1.0))
+            .collect::<Vec<_>>();
+        Self::with_knots(
+            &points,
+            uniform_knots(positions.len(), degree, is_clamped),
+            degree,
+        )
+    }
+
+    /// Create a NURBS curve from its weighted control points and knot vector
+    /// Fails unless there are `points + degree + 1` non-decreasing knots around a non-empty domain and positive weights.
+    pub fn with_knots(points: &[WeightedPoint], knots: Vec<f32>, degree: usize) -> Option<Self> {
+        if degree == 0
+            || points.len() <= degree
+            || knots.len() != points.len() + degree + 1
+            || knots.windows(2).any(|w| w[0] > w[1])
+            || knots[degree] >= knots[points.len()]
+            || points.iter().any(|(_, weight)| *weight <= 0.0)
+        {
+            return None;
+        }
+        let last = points.len() - 1;
+        Some(Self {
+            points: points
+                .iter()
+                .enumerate()
+                .map(|(i, (position, weight))| {
+                    let color = if i == 0 || i == last {
+                        COLOR_BLUE
+                    } else {
+                        COLOR_LIGHT
+                    };
+                    BasicPoint {
+                        weight: *weight,
+                        ..BasicPoint::new(*position, color)
+                    }
+                })
+                .collect(),
+            knots,
+            degree,
+            bezier_cache: RefCell::new(None),
+        })
+    }
+
+    /// Full circle as a quadratic NURBS curve
+    /// The control points are the corners and the middles of the edges of the enclosing square, the corners weighted
+    /// by the cosine of 45°.
+    pub fn circle(center: Vector2, radius: f32) -> Self {
+        let corner_weight = std::f32::consts::FRAC_1_SQRT_2;
+        let points = [
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (-1.0, 1.0),
+            (-1.0, 0.0),
+            (-1.0, -1.0),
+            (0.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 0.0),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| {
+            let weight = if i % 2 == 1 { corner_weight } else { 1.0 };
+            (center + Vector2::new(*x, *y) * radius, weight)
+        })
+        .collect::<Vec<_>>();
+        let knots = vec![
+            0.0, 0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0,
+        ];
+        Self::with_knots(&points, knots, 2).unwrap()
+    }
+
+    pub fn points(&self) -> &[BasicPoint] {
+        &self.points
+    }
+
+    pub fn points_mut(&mut self) -> &mut [BasicPoint] {
+        &mut self.points
+    }
+
+    pub fn knots(&self) -> &[f32] {
+        &self.knots
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Range of the parameter covered by the curve
+    pub fn domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+
+    /// Replace the knot vector with uniform knots
+    pub fn reset_knots(&mut self, is_clamped: bool) {
+        self.knots = uniform_knots(self.points.len(), self.degree, is_clamped);
+    }
+
+    /// Change the degree, resetting the knots (the degree must stay below the number of points)
+    pub fn set_degree(&mut self, degree: usize, is_clamped: bool) -> bool {
+        if degree == 0 || degree >= self.points.len() {
+            return false;
+        }
+        self.degree = degree;
+        self.reset_knots(is_clamped);
+        true
+    }
+
+    /// Move a knot, kept between its neighbours so that the knot vector stays non-decreasing
+    /// The knots bounding the domain can't meet, so that the curve never becomes empty.
+    pub fn set_knot(&mut self, index: usize, value: f32) {
+        let min = if index > 0 {
+            self.knots[index - 1]
+        } else {
+            0.0
+        };
+        let max = self.knots.get(index + 1).copied().unwrap_or(1.0);
+        let mut value = value.clamp(min, max);
+        if index == self.degree {
+            value = value.min(self.knots[self.points.len()] - MIN_DOMAIN_LENGTH);
+        } else if index == self.points.len() {
+            value = value.max(self.knots[self.degree] + MIN_DOMAIN_LENGTH);
+        }
+        if (min..=max).contains(&value) {
+            self.knots[index] = value;
+        }
+    }
+
+    /// Add a control point at the end, resetting the knots
+    pub fn push_point(&mut self, position: Vector2, is_clamped: bool) {
+        if let Some(last) = self.points.last_mut() {
+            last.color = COLOR_LIGHT;
+        }
+        self.points.push(BasicPoint::new(position, COLOR_BLUE));
+        self.reset_knots(is_clamped);
+    }
+
+    /// Remove the last control point, resetting the knots (the degree must stay below the number of points)
+    pub fn pop_point(&mut self, is_clamped: bool) -> bool {
+        if self.points.len() <= self.degree + 1 {
+            return false;
+        }
+        self.points.pop();
+        if let Some(last) = self.points.last_mut() {
+            last.color = COLOR_BLUE;
+        }
+        self.reset_knots(is_clamped);
+        true
+    }
+
+    /// Index of the knot span [knots[span], knots[span + 1]) holding u, the end of the domain belongs to the last span
+    pub fn find_span(&self, u: f32) -> usize {
+        let (start, end) = self.domain();
+        let u = u.clamp(start, end);
+        let last_span = (self.degree..self.points.len())
+            .rev()
+            .find(|&span| self.knots[span] < self.knots[span + 1])
+            .unwrap_or(self.degree);
+        (self.degree..=last_span)
+            .rev()
+            .find(|&span| self.knots[span] <= u)
+            .unwrap_or(self.degree)
+    }
+
+    /// Values of the degree + 1 basis functions that are not zero on the span (Cox-de Boor recursion)
+    /// The value i belongs to the control point span - degree + i.
+    pub fn basis_functions(&self, span: usize, u: f32) -> Vec<f32> {
+        let mut basis = vec![1.0; self.degree + 1];
+        let mut left = vec![0.0; self.degree + 1];
+        let mut right = vec![0.0; self.degree + 1];
+        for j in 1..=self.degree {
+            left[j] = u - self.knots[span + 1 - j];
+            right[j] = self.knots[span + j] - u;
+            let mut saved = 0.0;
+            for r in 0..j {
+                let denominator = right[r + 1] + left[j - r];
+                // 0/0 is taken as 0 on empty knot spans
+                let temp = if denominator == 0.0 {
+                    0.0
+                } else {
+                    basis[r] / denominator
+                };
+                basis[r] = saved + right[r + 1] * temp;
+                saved = left[j - r] * temp;
+            }
+            basis[j] = saved;
+        }
+        basis
+    }
+
+    /// Evaluate a point on the curve (u is clamped to the domain)
+    /// Rational curves are evaluated in homogeneous coordinates.
+    pub fn evaluate(&self, u: f32) -> Vector2 {
+        let (start, end) = self.domain();
+        let u = u.clamp(start, end);
+        let span = self.find_span(u);
+        let points = homogeneous_points(&self.points[span - self.degree..=span]);
+        self.basis_functions(span, u)
+            .iter()
+            .zip(points.iter())
+            .fold(
+                HomogeneousPoint {
+                    weighted_position: Vector2::zero(),
+                    weight: 0.0,
+                },
+                |sum, (b, point)| HomogeneousPoint {
+                    weighted_position: sum.weighted_position + point.weighted_position * *b,
+                    weight: sum.weight + point.weight * *b,
+                },
+            )
+            .position()
+    }
+
+    /// Values and derivatives up to the order n of the degree + 1 basis functions that are not zero on the span
+    /// Algorithm A2.3 of The NURBS Book: `derivatives[k][i]` is the derivative k of the basis function of the control
+    /// point span - degree + i (derivatives above the degree are zero).
+    pub fn basis_function_derivatives(&self, span: usize, u: f32, n: usize) -> Vec<Vec<f32>> {
+        let p = self.degree;
+        // Basis functions of every degree in the upper triangle and knot differences in the lower one
+        let mut ndu = vec![vec![1.0; p + 1]; p + 1];
+        let mut left = vec![0.0; p + 1];
+        let mut right = vec![0.0; p + 1];
+        for j in 1..=p {
+            left[j] = u - self.knots[span + 1 - j];
+            right[j] = self.knots[span + j] - u;
+            let mut saved = 0.0;
+            for r in 0..j {
+                ndu[j][r] = right[r + 1] + left[j - r];
+                let temp = ndu[r][j - 1] / ndu[j][r];
+                ndu[r][j] = saved + right[r + 1] * temp;
+                saved = left[j - r] * temp;
+            }
+            ndu[j][j] = saved;
+        }
+
+        let mut derivatives = vec![vec![0.0; p + 1]; n + 1];
+        for (j, value) in derivatives[0].iter_mut().enumerate() {
+            *value = ndu[j][p];
+        }
+        // Coefficients of the derivatives as differences of the basis functions of lower degrees
+        let mut a = [vec![0.0; p + 1], vec![0.0; p + 1]];
+        for r in 0..=p {
+            let (mut s1, mut s2) = (0, 1);
+            a[0][0] = 1.0;
+            for k in 1..=n.min(p) {
+                let mut d = 0.0;
+                let pk = p - k;
+                if r >= k {
+                    let rk = r - k;
+                    a[s2][0] = a[s1][0] / ndu[pk + 1][rk];
+                    d = a[s2][0] * ndu[rk][pk];
+                }
+                let j1 = if r + 1 >= k { 1 } else { k - r };
+                let j2 = if r <= pk + 1 { k - 1 } else { p - r };
+                for j in j1..=j2 {
+                    let rkj = r + j - k;
+                    a[s2][j] = (a[s1][j] - a[s1][j - 1]) / ndu[pk + 1][rkj];
+                    d += a[s2][j] * ndu[rkj][pk];
+                }
+                if r <= pk {
+                    a[s2][k] = -a[s1][k - 1] / ndu[pk + 1][r];
+                    d += a[s2][k] * ndu[r][pk];
+                }
+                derivatives[k][r] = d;
+                std::mem::swap(&mut s1, &mut s2);
+            }
+        }
+        let mut factor = p as f32;
+        for (k, derivative) in derivatives.iter_mut().enumerate().skip(1).take(p) {
+            for value in derivative.iter_mut() {
+                *value *= factor;
+            }
+            factor *= (p - k) as f32;
+        }
+        derivatives
+    }
+
+    /// First and second derivatives of the curve along u (u is clamped to the domain)
+    /// The homogeneous curve and its derivatives come from the basis function derivatives, then the quotient rule
+    /// gives the derivatives of the rational curve (algorithm A4.2 of The NURBS Book).
+    pub fn derivatives(&self, u: f32) -> (Vector2, Vector2) {
+        let (start, end) = self.domain();
+        let u = u.clamp(start, end);
+        let span = self.find_span(u);
+        let points = homogeneous_points(&self.points[span - self.degree..=span]);
+        let homogeneous = self
+            .basis_function_derivatives(span, u, 2)
+            .iter()
+            .map(|basis| {
+                basis.iter().zip(points.iter()).fold(
+                    HomogeneousPoint {
+                        weighted_position: Vector2::zero(),
+                        weight: 0.0,
+                    },
+                    |sum, (b, point)| HomogeneousPoint {
+                        weighted_position: sum.weighted_position + point.weighted_position * *b,
+                        weight: sum.weight + point.weight * *b,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let weight = homogeneous[0].weight;
+        let position = homogeneous[0].position();
+        let first =
+            (homogeneous[1].weighted_position - position * homogeneous[1].weight) * (1.0 / weight);
+        let second = (homogeneous[2].weighted_position
+            - first * (2.0 * homogeneous[1].weight)
+            - position * homogeneous[2].weight)
+            * (1.0 / weight);
+        (first, second)
+    }
+
+    /// Insert a knot without changing the curve (Boehm's algorithm in homogeneous coordinates)
+    /// Fails outside the domain or when the knot is already repeated degree times.
+    pub fn insert_knot(&mut self, u: f32) -> bool {
+        let (start, end) = self.domain();
+        let multiplicity = self.knots.iter().filter(|knot| **knot == u).count();
+        if u < start || u > end || multiplicity >= self.degree {
+            return false;
+        }
+        let span = self.find_span(u);
+        let points = homogeneous_points(&self.points);
+        let inserted = (span + 1 - self.degree..=span)
+            .map(|i| {
+                let alpha = (u - self.knots[i]) / (self.knots[i + self.degree] - self.knots[i]);
+                let point = points[i - 1].lerp(&points[i], alpha);
+                BasicPoint {
+                    weight: point.weight,
+                    ..BasicPoint::new(point.position(), COLOR_LIGHT)
+                }
+            })
+            .collect::<Vec<_>>();
+        self.points.splice(span + 1 - self.degree..span, inserted);
+        self.knots.insert(span + 1, u);
+        true
+    }
+
+    /// Parameter ranges of the non-empty knot spans of the domain
+    pub fn knot_spans(&self) -> Vec<(f32, f32)> {
+        (self.degree..self.points.len())
+            .map(|span| (self.knots[span], self.knots[span + 1]))
+            .filter(|(u0, u1)| u0 < u1)
+            .collect()
+    }
+
+    /// Knot spans of the domain with the control points of the curve on each one as a rational Bezier curve
+    /// Once every knot of the domain is repeated degree times, the points of each span are its Bezier points.
+    /// The decomposition is kept until the points, weights, knots or degree change.
+    fn bezier_spans(&self) -> Vec<((f32, f32), Vec<WeightedPoint>)> {
+        if let Some(cache) = self.bezier_cache.borrow().as_ref() {
+            let is_valid = cache.degree == self.degree
+                && cache.knots == self.knots
+                && cache.points.len() == self.points.len()
+                && cache.points.iter().zip(self.points.iter()).all(
+                    |((position, weight), point)| {
+                        *position == point.position && *weight == point.weight
+                    },
+                );
+            if is_valid {
+                return cache.spans.clone();
+            }
+        }
+        let spans = self.refined_bezier_spans();
+        *self.bezier_cache.borrow_mut() = Some(BezierCache {
+            points: self
+                .points
+                .iter()
+                .map(|point| (point.position, point.weight))
+                .collect(),
+            knots: self.knots.clone(),
+            degree: self.degree,
+            spans: spans.clone(),
+        });
+        spans
+    }
+
+    fn refined_bezier_spans(&self) -> Vec<((f32, f32), Vec<WeightedPoint>)> {
+        let (start, end) = self.domain();
+        let mut knots = self
+            .knots
+            .iter()
+            .copied()
+            .filter(|knot| *knot >= start && *knot <= end)
+            .collect::<Vec<_>>();
+        knots.dedup();
+        let mut refined = self.clone();
+        for u in knots {
+            while refined.insert_knot(u) {}
+        }
+        (refined.degree..refined.points.len())
+            .filter(|&span| refined.knots[span] < refined.knots[span + 1])
+            .map(|span| {
+                (
+                    (refined.knots[span], refined.knots[span + 1]),
+                    refined.points[span - refined.degree..=span]
+                        .iter()
+                        .map(|point| (point.position, point.weight))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Rational Bezier curves following the curve, one for each non-empty knot span of the domain
+    pub fn to_rational_beziers(&self) -> Vec<Vec<WeightedPoint>> {
+        self.bezier_spans()
+            .into_iter()
+            .map(|(_, points)| points)
+            .collect()
+    }
+
+    /// Points of the curve at each distinct knot of the domain
+    pub fn knot_points(&self) -> Vec<Vector2> {
+        let mut knots = self.knots[self.degree..=self.points.len()].to_vec();
+        knots.dedup();
+        knots.into_iter().map(|u| self.evaluate(u)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{evalute_bezier_curve, rational_derivatives};
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn positions() -> Vec<Vector2> {
+        vec![
+            Vector2::new(100.0, 500.0),
+            Vector2::new(300.0, 100.0),
+            Vector2::new(500.0, 600.0),
+            Vector2::new(700.0, 200.0),
+            Vector2::new(900.0, 500.0),
+            Vector2::new(1100.0, 300.0),
+        ]
+    }
+
+    #[test]
+    fn basis_is_a_partition_of_unity() {
+        for degree in 1..=4 {
+            for is_clamped in [true, false] {
+                let spline = BSpline::new(&positions(), degree, is_clamped).unwrap();
+                let (start, end) = spline.domain();
+                for i in 0..=20 {
+                    let u = start + (end - start) * i as f32 / 20.0;
+                    let basis = spline.basis_functions(spline.find_span(u), u);
+                    assert_eq!(basis.len(), degree + 1);
+                    assert!(basis.iter().all(|b| *b >= -1e-6));
+                    assert!((basis.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+                    // So the derivatives of the basis functions sum to zero
+                    let derivatives = spline.basis_function_derivatives(spline.find_span(u), u, 2);
+                    for (value, b) in derivatives[0].iter().zip(basis.iter()) {
+                        assert!((value - b).abs() < 1e-6);
+                    }
+                    for derivative in &derivatives[1..] {
+                        assert!(derivative.iter().sum::<f32>().abs() < 1e-2);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clamped_curve_interpolates_its_ends() {
+        let positions = positions();
+        let spline = BSpline::new(&positions, 3, true).unwrap();
+        assert_eq!(spline.domain(), (0.0, 1.0));
+        assert!(spline.evaluate(0.0).distance_to(positions[0]) < 1e-3);
+        assert!(spline.evaluate(1.0).distance_to(positions[5]) < 1e-3);
+
+        // With as many points as the order, a clamped B-spline is a Bezier curve
+        let bezier = BSpline::new(&positions[..4], 3, true).unwrap();
+        for i in 0..=10 {
+            let u = i as f32 / 10.0;
+            assert!(bezier
+                .evaluate(u)
+                .distance_to(evalute_bezier_curve(&positions[..4], u))
+                .lt(&1e-2));
+        }
+    }
+
+    #[test]
+    fn uniform_quadratic_passes_through_midpoints() {
+        let positions = positions();
+        let spline = BSpline::new(&positions, 2, false).unwrap();
+        let knot_points = spline.knot_points();
+        assert_eq!(knot_points.len(), positions.len() - 1);
+        for (point, pair) in knot_points.iter().zip(positions.windows(2)) {
+            assert!(point.distance_to((pair[0] + pair[1]) * 0.5) < 1e-2);
+        }
+    }
+
+    #[test]
+    fn knots_stay_sorted() {
+        let mut spline = BSpline::new(&positions(), 3, true).unwrap();
+        spline.set_knot(5, 0.0);
+        spline.set_knot(4, 0.9);
+        assert!(spline.knots().windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(spline.knots()[4], spline.knots()[5]);
+        // A knot repeated degree times makes the curve go through a control point
+        let mut spline = BSpline::new(&positions(), 2, true).unwrap();
+        spline.set_knot(4, spline.knots()[3]);
+        assert!(spline
+            .evaluate(spline.knots()[3])
+            .distance_to(positions()[2])
+            .lt(&1e-3));
+    }
+
+    #[test]
+    fn domain_stays_non_empty() {
+        for is_clamped in [true, false] {
+            let mut spline = BSpline::new(&positions(), 3, is_clamped).unwrap();
+            // Drag every knot across the whole knot vector, one way then the other
+            for index in 0..spline.knots().len() {
+                for i in (0..=50).chain((0..=50).rev()) {
+                    spline.set_knot(index, i as f32 / 50.0);
+                    let (start, end) = spline.domain();
+                    assert!(start < end);
+                    assert!(spline.knots().windows(2).all(|w| w[0] <= w[1]));
+                    let position = spline.evaluate((start + end) * 0.5);
+                    assert!(position.x.is_finite() && position.y.is_finite());
+                }
+            }
+        }
+    }
+
+    /// Quadratic NURBS circle inscribed in an equilateral triangle, the vertices weighted by the cosine of 60°
+    fn triangle_circle(center: Vector2, radius: f32) -> BSpline {
+        let points = (0..7)
+            .map(|i| {
+                let angle = PI / 2.0 + PI / 3.0 * i as f32;
+                let direction = Vector2::new(angle.cos(), angle.sin());
+                if i % 2 == 1 {
+                    (center + direction * (2.0 * radius), 0.5)
+                } else {
+                    (center + direction * radius, 1.0)
+                }
+            })
+            .collect::<Vec<_>>();
+        let knots = vec![
+            0.0,
+            0.0,
+            0.0,
+            1.0 / 3.0,
+            1.0 / 3.0,
+            2.0 / 3.0,
+            2.0 / 3.0,
+            1.0,
+            1.0,
+            1.0,
+        ];
+        BSpline::with_knots(&points, knots, 2).unwrap()
+    }
+
+    fn assert_on_circle(spline: &BSpline, center: Vector2, radius: f32) {
+        for i in 0..=100 {
+            let u = i as f32 / 100.0;
+            let position = spline.evaluate(u);
+            assert!((position.distance_to(center) - radius).abs() < 1e-2);
+            // The tangent of a circle is perpendicular to the radius
+            let (first, _) = spline.derivatives(u);
+            assert!(
+                first
+                    .normalized()
+                    .dot((position - center).normalized())
+                    .abs()
+                    < 1e-3
+            );
+        }
+    }
+
+    #[test]
+    fn nurbs_circles_are_exact() {
+        let center = Vector2::new(400.0, 300.0);
+        let square_circle = BSpline::circle(center, 200.0);
+        assert_on_circle(&square_circle, center, 200.0);
+        assert!(
+            square_circle
+                .evaluate(0.25)
+                .distance_to(Vector2::new(400.0, 500.0))
+                < 1e-3
+        );
+        let triangle_circle = triangle_circle(center, 200.0);
+        assert_on_circle(&triangle_circle, center, 200.0);
+
+        // Each knot span is an exact rational Bezier arc
+        for (spline, span_count) in [(square_circle, 4), (triangle_circle, 3)] {
+            let beziers = spline.to_rational_beziers();
+            assert_eq!(beziers.len(), span_count);
+            for bezier in beziers.iter() {
+                assert_eq!(bezier.len(), 3);
+                for i in 0..=10 {
+                    let position = evalute_bezier_curve(bezier, i as f32 / 10.0);
+                    assert!((position.distance_to(center) - 200.0).abs() < 1e-2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        let mut spline = BSpline::new(&positions(), 3, false).unwrap();
+        spline.points_mut()[2].weight = 3.0;
+        spline.points_mut()[4].weight = 0.5;
+        const H: f32 = 1e-3;
+        let (start, end) = spline.domain();
+        for i in 1..10 {
+            let u = start + (end - start) * i as f32 / 10.0;
+            let (first, second) = spline.derivatives(u);
+            let first_difference = (spline.evaluate(u + H) - spline.evaluate(u - H)) * (0.5 / H);
+            assert!(first.distance_to(first_difference) < 1e-2 * first.length());
+            let second_difference =
+                (spline.derivatives(u + H).0 - spline.derivatives(u - H).0) * (0.5 / H);
+            assert!(second.distance_to(second_difference) < 2e-2 * second.length());
+        }
+
+        // Same derivatives as the Bezier segment of the knot span
+        let beziers = spline.to_rational_beziers();
+        for (span, bezier) in beziers.iter().enumerate() {
+            let (u0, u1) = (spline.knots()[span + 3], spline.knots()[span + 4]);
+            let u = u0 + (u1 - u0) * 0.3;
+            let (first, second) = spline.derivatives(u);
+            let (bezier_first, bezier_second) = rational_derivatives(bezier, 0.3);
+            let scale = 1.0 / (u1 - u0);
+            assert!(first.distance_to(bezier_first * scale) < 1e-3 * first.length());
+            assert!(second.distance_to(bezier_second * (scale * scale)) < 1e-3 * second.length());
+        }
+    }
+
+    #[test]
+    fn bezier_decomposition_follows_edits() {
+        let mut spline = BSpline::new(&positions(), 3, true).unwrap();
+        let beziers = spline.to_rational_beziers();
+        assert_eq!(spline.to_rational_beziers(), beziers);
+        spline.points_mut()[5].position = Vector2::new(1200.0, 400.0);
+        assert_eq!(
+            spline.to_rational_beziers().last().unwrap()[3],
+            (Vector2::new(1200.0, 400.0), 1.0)
+        );
+        spline.points_mut()[5].weight = 2.0;
+        assert_eq!(spline.to_rational_beziers().last().unwrap()[3].1, 2.0);
+        spline.set_knot(4, 0.4);
+        assert_ne!(spline.to_rational_beziers()[0], beziers[0]);
+        spline.set_degree(2, true);
+        assert_eq!(spline.to_rational_beziers()[0].len(), 3);
+    }
+
+    #[test]
+    fn knot_insertion_keeps_the_curve() {
+        let mut spline = BSpline::new(&positions(), 3, false).unwrap();
+        spline.points_mut()[1].weight = 2.0;
+        let original = spline.clone();
+        let (start, end) = spline.domain();
+        let u = start + (end - start) * 0.3;
+        for _ in 0..3 {
+            assert!(spline.insert_knot(u));
+        }
+        // A knot can't be repeated more than degree times
+        assert!(!spline.insert_knot(u));
+        assert!(!spline.insert_knot(end + 0.1));
+        assert_eq!(spline.points().len(), original.points().len() + 3);
+        assert_eq!(spline.knots().len(), spline.points().len() + 4);
+        assert_eq!(spline.domain(), original.domain());
+        for i in 0..=20 {
+            let u = start + (end - start) * i as f32 / 20.0;
+            assert!(spline.evaluate(u).distance_to(original.evaluate(u)) < 1e-2);
+        }
+        // A knot repeated degree times makes the curve go through a control point
+        assert!(spline
+            .points()
+            .iter()
+            .any(|p| p.position.distance_to(spline.evaluate(u)) < 1e-2));
+
+        // The Bezier segments of an unclamped curve follow it on the whole domain
+        let beziers = original.to_rational_beziers();
+        assert_eq!(beziers.len(), 3);
+        for (span, bezier) in beziers.iter().enumerate() {
+            let (u0, u1) = (original.knots()[span + 3], original.knots()[span + 4]);
+            for i in 0..=10 {
+                let t = i as f32 / 10.0;
+                let position = original.evaluate(u0 + (u1 - u0) * t);
+                assert!(position.distance_to(evalute_bezier_curve(bezier, t)) < 1e-2);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use raylib::prelude::*;
+
+use super::{BasicPoint, Point};
+use crate::colors::*;
+
+/// Shortest knot interval, so that coincident points don't divide by zero
+const MIN_KNOT_INTERVAL: f32 = 1e-4;
+
+/// Interpolating spline going through each of its points
+/// The knot interval between two points is their distance raised to alpha: 0 gives the uniform parameterization,
+/// 0.5 the centripetal one (no cusps nor loops inside a segment) and 1 the chordal one.
+#[derive(Debug, Clone)]
+pub struct CatmullRomSpline {
+    points: Vec<BasicPoint>,
+    pub alpha: f32,
+}
+impl CatmullRomSpline {
+    pub fn new(positions: &[Vector2], alpha: f32) -> Self {
+        let mut spline = Self {
+            points: Vec::with_capacity(positions.len()),
+            alpha,
+        };
+        for position in positions {
+            spline.push_point(*position);
+        }
+        spline
+    }
+
+    pub fn points(&self) -> &[BasicPoint] {
+        &self.points
+    }
+
+    pub fn points_mut(&mut self) -> &mut [BasicPoint] {
+        &mut self.points
+    }
+
+    /// Add a point at the end of the spline
+    pub fn push_point(&mut self, position: Vector2) {
+        self.points.push(BasicPoint::new(position, COLOR_BLUE));
+    }
+
+    /// Remove the last point (at least 2 points are kept)
+    pub fn pop_point(&mut self) -> bool {
+        if self.points.len() <= 2 {
+            return false;
+        }
+        self.points.pop();
+        true
+    }
+
+    fn knot_interval(&self, from: Vector2, to: Vector2) -> f32 {
+        from.distance_to(to).powf(self.alpha).max(MIN_KNOT_INTERVAL)
+    }
+
+    /// Cubic Bezier segments between each pair of consecutive points
+    /// Both ends get a phantom point mirroring their neighbour so that the first and last segments are defined too.
+    pub fn to_cubics(&self) -> Vec<[Vector2; 4]> {
+        let positions = self
+            .points
+            .iter()
+            .map(|p| p.get_position())
+            .collect::<Vec<_>>();
+        let n = positions.len();
+        if n < 2 {
+            return Vec::new();
+        }
+        let position = |i: isize| {
+            if i < 0 {
+                positions[0] * 2.0 - positions[1]
+            } else if i as usize >= n {
+                positions[n - 1] * 2.0 - positions[n - 2]
+            } else {
+                positions[i as usize]
+            }
+        };
+        (0..n as isize - 1)
+            .map(|i| {
+                let (p0, p1, p2, p3) = (
+                    position(i - 1),
+                    position(i),
+                    position(i + 1),
+                    position(i + 2),
+                );
+                let d0 = self.knot_interval(p0, p1);
+                let d1 = self.knot_interval(p1, p2);
+                let d2 = self.knot_interval(p2, p3);
+                // Tangents of the segment scaled to its knot interval
+                let m1 = ((p1 - p0) * (1.0 / d0) - (p2 - p0) * (1.0 / (d0 + d1))
+                    + (p2 - p1) * (1.0 / d1))
+                    * d1;
+                let m2 = ((p2 - p1) * (1.0 / d1) - (p3 - p1) * (1.0 / (d1 + d2))
+                    + (p3 - p2) * (1.0 / d2))
+                    * d1;
+                [p1, p1 + m1 * (1.0 / 3.0), p2 - m2 * (1.0 / 3.0), p2]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::evalute_bezier_curve;
+    use super::*;
+
+    fn positions() -> Vec<Vector2> {
+        vec![
+            Vector2::new(100.0, 500.0),
+            Vector2::new(150.0, 100.0),
+            Vector2::new(500.0, 600.0),
+            Vector2::new(520.0, 580.0),
+            Vector2::new(900.0, 200.0),
+        ]
+    }
+
+    /// Barry and Goldman's pyramidal formulation of the segment between p1 and p2
+    fn barry_goldman(p: [Vector2; 4], alpha: f32, t: f32) -> Vector2 {
+        let mut knots = [0.0; 4];
+        for i in 1..4 {
+            knots[i] = knots[i - 1] + p[i - 1].distance_to(p[i]).powf(alpha);
+        }
+        let u = knots[1] + (knots[2] - knots[1]) * t;
+        let lerp = |a: Vector2, b: Vector2, k0: f32, k1: f32| {
+            a * ((k1 - u) / (k1 - k0)) + b * ((u - k0) / (k1 - k0))
+        };
+        let a1 = lerp(p[0], p[1], knots[0], knots[1]);
+        let a2 = lerp(p[1], p[2], knots[1], knots[2]);
+        let a3 = lerp(p[2], p[3], knots[2], knots[3]);
+        let b1 = lerp(a1, a2, knots[0], knots[2]);
+        let b2 = lerp(a2, a3, knots[1], knots[3]);
+        lerp(b1, b2, knots[1], knots[2])
+    }
+
+    #[test]
+    fn spline_goes_through_its_points() {
+        let positions = positions();
+        for alpha in [0.0, 0.5, 1.0] {
+            let cubics = CatmullRomSpline::new(&positions, alpha).to_cubics();
+            assert_eq!(cubics.len(), positions.len() - 1);
+            for (cubic, pair) in cubics.iter().zip(positions.windows(2)) {
+                assert_eq!(cubic[0], pair[0]);
+                assert_eq!(cubic[3], pair[1]);
+            }
+            // Tangents are continuous across the joins
+            for pair in cubics.windows(2) {
+                let incoming = (pair[0][3] - pair[0][2]).normalized();
+                let outgoing = (pair[1][1] - pair[1][0]).normalized();
+                assert!(incoming.distance_to(outgoing) < 1e-3);
+            }
+        }
+
+        // Uniform tangents are half the vector between the neighbours
+        let uniform = CatmullRomSpline::new(&positions, 0.0).to_cubics();
+        let expected = positions[1] + (positions[2] - positions[0]) * (1.0 / 6.0);
+        assert!(uniform[1][1].distance_to(expected) < 1e-3);
+    }
+
+    #[test]
+    fn cubics_match_the_pyramidal_formulation() {
+        let positions = positions();
+        for alpha in [0.0, 0.5, 1.0] {
+            let cubics = CatmullRomSpline::new(&positions, alpha).to_cubics();
+            for (i, cubic) in cubics.iter().enumerate().skip(1).take(positions.len() - 3) {
+                let p = [
+                    positions[i - 1],
+                    positions[i],
+                    positions[i + 1],
+                    positions[i + 2],
+                ];
+                for j in 0..=10 {
+                    let t = j as f32 / 10.0;
+                    let expected = barry_goldman(p, alpha, t);
+                    assert!(evalute_bezier_curve(cubic, t).distance_to(expected) < 1e-2);
+                }
+            }
+        }
+
+        // Coincident points don't break the spline
+        let mut spline = CatmullRomSpline::new(&positions, 0.5);
+        spline.push_point(positions[4]);
+        assert!(spline
+            .to_cubics()
+            .iter()
+            .flatten()
+            .all(|p| p.x.is_finite() && p.y.is_finite()));
+        assert!(spline.pop_point());
+    }
+}
@@ -0,0 +1,123 @@
+use raylib::prelude::*;
+
+use super::{distance_to_polyline, rational_bezier_split_at, Point, WeightedPoint};
+
+/// Subdivision limit (up to 2^16 chords per curve)
+const MAX_FLATTEN_DEPTH: u32 = 16;
+
+/// Vertex of a flattened curve with its parameter on the curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolylinePoint {
+    pub t: f32,
+    pub position: Vector2,
+}
+
+/// Maximum distance between the control points and the chord, which bounds the distance between the curve and the chord
+/// (rational curves stay in the convex hull of their control points too, as long as the weights are positive)
+fn chord_deviation(points: &[WeightedPoint]) -> f32 {
+    let chord = [points[0].0, points[points.len() - 1].0];
+    points
+        .iter()
+        .map(|(p, _)| distance_to_polyline(*p, &chord))
+        .fold(0.0, f32::max)
+}
+
+fn flatten_in(
+    points: &[WeightedPoint],
+    (t0, t1): (f32, f32),
+    tolerance: f32,
+    depth: u32,
+    polyline: &mut Vec<PolylinePoint>,
+) {
+    if depth == 0 || chord_deviation(points) <= tolerance {
+        polyline.push(PolylinePoint {
+            t: t1,
+            position: points[points.len() - 1].0,
+        });
+        return;
+    }
+    let (left, right) = rational_bezier_split_at(points, 0.5);
+    let middle = (t0 + t1) * 0.5;
+    flatten_in(&left, (t0, middle), tolerance, depth - 1, polyline);
+    flatten_in(&right, (middle, t1), tolerance, depth - 1, polyline);
+}
+
+/// Polyline following a curve of any degree within the tolerance, from t = 0 to t = 1
+/// The curve is split in halves until the control points of each piece are close enough to its chord,
+/// so small or straight curves need only a few vertices while large and tight ones get more.
+pub fn bezier_flatten(points: &[impl Point], tolerance: f32) -> Vec<PolylinePoint> {
+    let points = points
+        .iter()
+        .map(|p| (p.get_position(), p.get_weight()))
+        .collect::<Vec<_>>();
+    let Some((start, _)) = points.first() else {
+        return Vec::new();
+    };
+    let mut polyline = vec![PolylinePoint {
+        t: 0.0,
+        position: *start,
+    }];
+    if points.len() > 1 {
+        flatten_in(
+            &points,
+            (0.0, 1.0),
+            tolerance,
+            MAX_FLATTEN_DEPTH,
+            &mut polyline,
+        );
+    }
+    polyline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::evalute_bezier_curve;
+    use super::*;
+
+    #[test]
+    fn straight_line_is_a_single_chord() {
+        let line = [Vector2::new(0.0, 0.0), Vector2::new(500.0, 250.0)];
+        let polyline = bezier_flatten(&line, 0.1);
+        assert_eq!(polyline.len(), 2);
+        assert_eq!(polyline[1].t, 1.0);
+        assert_eq!(polyline[1].position, line[1]);
+    }
+
+    #[test]
+    fn polyline_stays_within_tolerance() {
+        let cubic = [
+            Vector2::new(300.0, 600.0),
+            Vector2::new(600.0, 300.0),
+            Vector2::new(900.0, 300.0),
+            Vector2::new(1200.0, 600.0),
+        ];
+        let tolerance = 0.25;
+        let polyline = bezier_flatten(&cubic, tolerance);
+        let positions = polyline.iter().map(|p| p.position).collect::<Vec<_>>();
+        for i in 0..=1000 {
+            let position = evalute_bezier_curve(&cubic, i as f32 / 1000.0);
+            assert!(distance_to_polyline(position, &positions) <= tolerance + 1e-3);
+        }
+        for pair in polyline.windows(2) {
+            assert!(pair[0].t < pair[1].t);
+        }
+        for vertex in polyline.iter() {
+            assert!(evalute_bezier_curve(&cubic, vertex.t).distance_to(vertex.position) < 1e-2);
+        }
+
+        // The same curve ten times smaller needs fewer vertices
+        let small = cubic.map(|p| p * 0.1);
+        assert!(bezier_flatten(&small, tolerance).len() < polyline.len());
+        // Control points overshooting the chord are followed too
+        let overshoot = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(300.0, 0.0),
+            Vector2::new(100.0, 0.0),
+        ];
+        let far_end = bezier_flatten(&overshoot, tolerance)
+            .iter()
+            .map(|p| p.position.x)
+            .fold(0.0, f32::max);
+        assert!(far_end > 150.0);
+    }
+}
@@ -0,0 +1,577 @@
+use raylib::prelude::*;
+
+use super::{
+    bezier_bounding_box, bezier_derivative_roots, bezier_roots, evalute_bezier_curve,
+    rational_bezier_split_at, rational_bezier_sub_curve, Point, WeightedPoint,
+};
+
+/// Sub-curves closer than that to their chord are intersected as line segments
+const FLATNESS_TOLERANCE: f32 = 1e-3;
+const MAX_SUBDIVISION_DEPTH: u32 = 48;
+/// Distance under which flat pieces of two curves lie on each other (the pieces are within the flatness tolerance
+/// of their chords)
+const OVERLAP_TOLERANCE: f32 = 2.0 * FLATNESS_TOLERANCE;
+/// Intersections closer than that (on both curves) are the same one
+const T_TOLERANCE: f32 = 1e-4;
+/// Distance under which a curve touching a line without crossing it counts as an intersection
+const TOUCH_TOLERANCE: f32 = 1e-2;
+
+/// Crossing point of two curves with its parameter on each of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub t1: f32,
+    pub t2: f32,
+    pub position: Vector2,
+}
+
+/// Part of a curve between t0 and t1 of the original curve (with the weights of rational curves)
+struct SubCurve {
+    points: Vec<WeightedPoint>,
+    t0: f32,
+    t1: f32,
+    bounding_box: Rectangle,
+}
+impl SubCurve {
+    fn new(points: Vec<WeightedPoint>, t0: f32, t1: f32) -> Self {
+        let bounding_box = bezier_bounding_box(&points).unwrap();
+        Self {
+            points,
+            t0,
+            t1,
+            bounding_box,
+        }
+    }
+
+    fn split(&self) -> (Self, Self) {
+        let (left, right) = rational_bezier_split_at(&self.points, 0.5);
+        let middle = (self.t0 + self.t1) * 0.5;
+        (
+            Self::new(left, self.t0, middle),
+            Self::new(right, middle, self.t1),
+        )
+    }
+
+    fn size(&self) -> f32 {
+        self.bounding_box.width.max(self.bounding_box.height)
+    }
+
+    /// Whether every control point is close to the chord
+    /// With positive weights, rational curves stay in the convex hull of their control points too.
+    fn is_flat(&self) -> bool {
+        let (start, end) = self.chord();
+        let chord = end - start;
+        let chord_length = chord.length();
+        self.points.iter().all(|(p, _)| {
+            let offset = *p - start;
+            let distance = if chord_length < f32::EPSILON {
+                offset.length()
+            } else {
+                (chord.x * offset.y - chord.y * offset.x).abs() / chord_length
+            };
+            distance <= FLATNESS_TOLERANCE
+        })
+    }
+
+    fn chord(&self) -> (Vector2, Vector2) {
+        (self.points[0].0, self.points[self.points.len() - 1].0)
+    }
+
+    fn global_t(&self, local_t: f32) -> f32 {
+        self.t0 + (self.t1 - self.t0) * local_t
+    }
+}
+
+fn boxes_overlap(a: Rectangle, b: Rectangle) -> bool {
+    a.x <= b.x + b.width + FLATNESS_TOLERANCE
+        && b.x <= a.x + a.width + FLATNESS_TOLERANCE
+        && a.y <= b.y + b.height + FLATNESS_TOLERANCE
+        && b.y <= a.y + a.height + FLATNESS_TOLERANCE
+}
+
+/// Parameters of the crossing point of two line segments (parallel segments never cross)
+fn segment_intersection(
+    (a_start, a_end): (Vector2, Vector2),
+    (b_start, b_end): (Vector2, Vector2),
+) -> Option<(f32, f32)> {
+    const EPSILON: f32 = 1e-6;
+    let r = a_end - a_start;
+    let s = b_end - b_start;
+    let denominator = r.x * s.y - r.y * s.x;
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+    let offset = b_start - a_start;
+    let u = (offset.x * s.y - offset.y * s.x) / denominator;
+    let v = (offset.x * r.y - offset.y * r.x) / denominator;
+    ((-EPSILON..=1.0 + EPSILON).contains(&u) && (-EPSILON..=1.0 + EPSILON).contains(&v))
+        .then_some((u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)))
+}
+
+/// Whether two line segments lie on each other along more than a point
+/// The part of the first segment facing the second one must be close to the line of the second one at both ends.
+fn segments_overlap(
+    (a_start, a_end): (Vector2, Vector2),
+    (b_start, b_end): (Vector2, Vector2),
+) -> bool {
+    let r = a_end - a_start;
+    let s = b_end - b_start;
+    let (a_length, b_length) = (r.length(), s.length());
+    if a_length < f32::EPSILON || b_length < f32::EPSILON {
+        return false;
+    }
+    // Range of the first segment covered by the projection of the second one
+    let project = |p: Vector2| (p - a_start).dot(r) / (a_length * a_length);
+    let (u0, u1) = (project(b_start), project(b_end));
+    let (start, end) = (u0.min(u1).max(0.0), u0.max(u1).min(1.0));
+    if (end - start) * a_length <= OVERLAP_TOLERANCE {
+        return false;
+    }
+    let distance_to_b = |p: Vector2| {
+        let offset = p - b_start;
+        (s.x * offset.y - s.y * offset.x).abs() / b_length
+    };
+    distance_to_b(a_start + r * start) <= OVERLAP_TOLERANCE
+        && distance_to_b(a_start + r * end) <= OVERLAP_TOLERANCE
+}
+
+/// Parameter ranges of two pieces of curves lying on each other
+type Overlap = ((f32, f32), (f32, f32));
+
+fn subdivide(
+    a: &SubCurve,
+    b: &SubCurve,
+    depth: u32,
+    intersections: &mut Vec<(f32, f32)>,
+    overlaps: &mut Vec<Overlap>,
+) {
+    if !boxes_overlap(a.bounding_box, b.bounding_box) {
+        return;
+    }
+    if depth == 0 || (a.is_flat() && b.is_flat()) {
+        if segments_overlap(a.chord(), b.chord()) {
+            overlaps.push(((a.t0, a.t1), (b.t0, b.t1)));
+        } else if let Some((u, v)) = segment_intersection(a.chord(), b.chord()) {
+            intersections.push((a.global_t(u), b.global_t(v)));
+        }
+        return;
+    }
+    // Always split the biggest curve to keep both sizes close
+    if a.size() >= b.size() {
+        let (left, right) = a.split();
+        subdivide(&left, b, depth - 1, intersections, overlaps);
+        subdivide(&right, b, depth - 1, intersections, overlaps);
+    } else {
+        let (left, right) = b.split();
+        subdivide(a, &left, depth - 1, intersections, overlaps);
+        subdivide(a, &right, depth - 1, intersections, overlaps);
+    }
+}
+
+fn weighted_points(points: &[impl Point]) -> Vec<WeightedPoint> {
+    points
+        .iter()
+        .map(|p| (p.get_position(), p.get_weight()))
+        .collect()
+}
+
+/// Every crossing point between two curves of any degree, sorted along the first curve
+/// Both curves are recursively split in halves while their bounding boxes overlap, until the pieces are flat enough to be intersected as line segments.
+/// Overlapping parts of the curves are not reported: flat pieces lying on each other are skipped, along with the
+/// crossings found where such pieces end.
+pub fn bezier_intersections(a: &[impl Point], b: &[impl Point]) -> Vec<Intersection> {
+    if a.len() < 2 || b.len() < 2 {
+        return Vec::new();
+    }
+    let a_points = weighted_points(a);
+    let b_points = weighted_points(b);
+    let mut parameters = Vec::new();
+    let mut overlaps = Vec::new();
+    subdivide(
+        &SubCurve::new(a_points.clone(), 0.0, 1.0),
+        &SubCurve::new(b_points.clone(), 0.0, 1.0),
+        MAX_SUBDIVISION_DEPTH,
+        &mut parameters,
+        &mut overlaps,
+    );
+    let is_in_range = |t: f32, (t0, t1): (f32, f32)| t0 - T_TOLERANCE <= t && t <= t1 + T_TOLERANCE;
+    parameters.retain(|(t1, t2)| {
+        !overlaps
+            .iter()
+            .any(|(a_range, b_range)| is_in_range(*t1, *a_range) && is_in_range(*t2, *b_range))
+    });
+
+    parameters.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    // Crossing points on the boundary of two pieces are found twice
+    parameters.dedup_by(|(a1, a2), (b1, b2)| {
+        (*a1 - *b1).abs() < T_TOLERANCE && (*a2 - *b2).abs() < T_TOLERANCE
+    });
+    parameters
+        .into_iter()
+        .map(|(t1, t2)| Intersection {
+            t1,
+            t2,
+            position: evalute_bezier_curve(&a_points, t1),
+        })
+        .collect()
+}
+
+/// Every point where a curve of any degree crosses itself (t1 < t2), sorted along the curve
+/// The curve is split where it changes direction on either axis: pieces monotone on both axes can't cross themselves,
+/// so only pairs of pieces are intersected (ignoring the end point shared by consecutive pieces).
+pub fn bezier_self_intersections(points: &[impl Point]) -> Vec<Intersection> {
+    if points.len() < 4 {
+        // Lines and parabolas never cross themselves
+        return Vec::new();
+    }
+    let points = weighted_points(points);
+    let weights = points.iter().map(|(_, w)| *w).collect::<Vec<_>>();
+    let mut splits = bezier_derivative_roots(
+        &points.iter().map(|(p, _)| p.x).collect::<Vec<_>>(),
+        &weights,
+    );
+    splits.extend(bezier_derivative_roots(
+        &points.iter().map(|(p, _)| p.y).collect::<Vec<_>>(),
+        &weights,
+    ));
+    splits.extend([0.0, 1.0]);
+    splits.sort_by(|a, b| a.total_cmp(b));
+    splits.dedup_by(|a, b| (*a - *b).abs() < T_TOLERANCE);
+    let pieces = splits
+        .windows(2)
+        .map(|w| (w[0], w[1], rational_bezier_sub_curve(&points, w[0], w[1])))
+        .collect::<Vec<_>>();
+
+    let mut intersections: Vec<Intersection> = Vec::new();
+    for (i, (a_start, a_end, a_points)) in pieces.iter().enumerate() {
+        for (b_start, b_end, b_points) in pieces.iter().skip(i + 1) {
+            for intersection in bezier_intersections(a_points, b_points) {
+                let t1 = a_start + (a_end - a_start) * intersection.t1;
+                let t2 = b_start + (b_end - b_start) * intersection.t2;
+                let is_known = intersections.iter().any(|known| {
+                    (known.t1 - t1).abs() < T_TOLERANCE && (known.t2 - t2).abs() < T_TOLERANCE
+                });
+                // Consecutive pieces always meet on their shared end point
+                if t2 - t1 > T_TOLERANCE && !is_known {
+                    intersections.push(Intersection {
+                        t1,
+                        t2,
+                        position: intersection.position,
+                    });
+                }
+            }
+        }
+    }
+    intersections.sort_by(|a, b| a.t1.total_cmp(&b.t1));
+    intersections
+}
+
+/// Extent of a straight line going through two points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Infinite in both directions
+    Line,
+    /// Starts at the first point and goes through the second one
+    Ray,
+    /// Between both points
+    Segment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    pub start: Vector2,
+    pub end: Vector2,
+    pub kind: LineKind,
+}
+impl Line {
+    pub fn new(start: Vector2, end: Vector2, kind: LineKind) -> Self {
+        Self { start, end, kind }
+    }
+
+    /// Whether the parameter along the line (0 at start, 1 at end) is part of it
+    fn contains(&self, line_t: f32) -> bool {
+        const EPSILON: f32 = 1e-6;
+        match self.kind {
+            LineKind::Line => true,
+            LineKind::Ray => line_t >= -EPSILON,
+            LineKind::Segment => (-EPSILON..=1.0 + EPSILON).contains(&line_t),
+        }
+    }
+}
+
+/// Crossing (or touching) point of a curve and a line with its parameter on both of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineIntersection {
+    pub t: f32,
+    /// Parameter along the line (0 at start, 1 at end)
+    pub line_t: f32,
+    pub position: Vector2,
+}
+
+/// Every point where a curve of any degree crosses or touches a line, sorted along the curve
+/// The signed distance of the curve to the line is itself a 1D (rational) Bezier curve whose control values are the distances of the control points:
+/// crossings are its roots and tangential touches are the roots of its derivative where the distance is close to zero.
+pub fn bezier_line_intersections(points: &[impl Point], line: Line) -> Vec<LineIntersection> {
+    let direction = line.end - line.start;
+    let length = direction.length();
+    if points.is_empty() || length < f32::EPSILON {
+        return Vec::new();
+    }
+    let points = weighted_points(points);
+    let weights = points.iter().map(|(_, w)| *w).collect::<Vec<_>>();
+    let distances = points
+        .iter()
+        .map(|(p, _)| {
+            let offset = *p - line.start;
+            (direction.x * offset.y - direction.y * offset.x) / length
+        })
+        .collect::<Vec<_>>();
+    let line_distance = |t: f32| {
+        let offset = evalute_bezier_curve(&points, t) - line.start;
+        ((direction.x * offset.y - direction.y * offset.x) / length).abs()
+    };
+
+    // The distance of a rational curve has the sign of its numerator (the weights are positive)
+    let mut parameters = bezier_roots(
+        &distances
+            .iter()
+            .zip(weights.iter())
+            .map(|(d, w)| d * w)
+            .collect::<Vec<_>>(),
+    );
+    // End points close to the line (and not exactly on it) are not roots
+    if distances[0].abs() < TOUCH_TOLERANCE {
+        parameters.push(0.0);
+    }
+    if distances[distances.len() - 1].abs() < TOUCH_TOLERANCE {
+        parameters.push(1.0);
+    }
+    parameters.extend(
+        bezier_derivative_roots(&distances, &weights)
+            .into_iter()
+            .filter(|t| line_distance(*t) < TOUCH_TOLERANCE),
+    );
+
+    parameters.sort_by(|a, b| a.total_cmp(b));
+    // Keep the closest to the line among parameters found several times
+    parameters.dedup_by(|a, b| {
+        let is_same = (*a - *b).abs() < T_TOLERANCE;
+        if is_same && line_distance(*a) < line_distance(*b) {
+            *b = *a;
+        }
+        is_same
+    });
+    // Rounding errors around a tangential touch can show up as two crossings on both sides of it
+    parameters.dedup_by(|a, b| {
+        let middle = (*a + *b) * 0.5;
+        let is_touch = [*a, *b, middle]
+            .iter()
+            .all(|t| line_distance(*t) < TOUCH_TOLERANCE);
+        if is_touch {
+            *b = middle;
+        }
+        is_touch
+    });
+    parameters
+        .into_iter()
+        .map(|t| {
+            let position = evalute_bezier_curve(&points, t);
+            LineIntersection {
+                t,
+                line_t: (position - line.start).dot(direction) / (length * length),
+                position,
+            }
+        })
+        .filter(|intersection| line.contains(intersection.line_t))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_intersections() {
+        let a = [Vector2::new(0.0, 0.0), Vector2::new(100.0, 100.0)];
+        let b = [Vector2::new(0.0, 100.0), Vector2::new(100.0, 0.0)];
+        let intersections = bezier_intersections(&a, &b);
+        assert_eq!(intersections.len(), 1);
+        assert!((intersections[0].t1 - 0.5).abs() < 1e-4);
+        assert!((intersections[0].t2 - 0.5).abs() < 1e-4);
+        assert!(
+            intersections[0]
+                .position
+                .distance_to(Vector2::new(50.0, 50.0))
+                < 1e-2
+        );
+
+        let parallel = [Vector2::new(0.0, 10.0), Vector2::new(100.0, 110.0)];
+        assert!(bezier_intersections(&a, &parallel).is_empty());
+    }
+
+    #[test]
+    fn cubic_crossing_a_wave() {
+        let arch = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 300.0),
+            Vector2::new(200.0, 300.0),
+            Vector2::new(300.0, 0.0),
+        ];
+        // Degree 5 wave crossing the arch several times
+        let wave = [
+            Vector2::new(-50.0, 100.0),
+            Vector2::new(50.0, 400.0),
+            Vector2::new(100.0, -200.0),
+            Vector2::new(200.0, 500.0),
+            Vector2::new(250.0, -200.0),
+            Vector2::new(350.0, 200.0),
+        ];
+        let intersections = bezier_intersections(&arch, &wave);
+        assert!(intersections.len() >= 2);
+        for intersection in intersections.iter() {
+            let on_arch = evalute_bezier_curve(&arch, intersection.t1);
+            let on_wave = evalute_bezier_curve(&wave, intersection.t2);
+            assert!(on_arch.distance_to(on_wave) < 1e-2);
+            assert!(on_arch.distance_to(intersection.position) < 1e-3);
+        }
+        for pair in intersections.windows(2) {
+            assert!(pair[0].t1 < pair[1].t1);
+        }
+
+        // Swapping the curves finds the same crossings
+        let swapped = bezier_intersections(&wave, &arch);
+        assert_eq!(swapped.len(), intersections.len());
+    }
+
+    #[test]
+    fn overlapping_curves_are_skipped() {
+        let arch = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 300.0),
+            Vector2::new(200.0, 300.0),
+            Vector2::new(300.0, 0.0),
+        ];
+        assert!(bezier_intersections(&arch, &arch).is_empty());
+        let part = rational_bezier_sub_curve(&weighted_points(&arch), 0.2, 0.7);
+        assert!(bezier_intersections(&arch, &part).is_empty());
+        assert!(bezier_intersections(&part, &arch).is_empty());
+        let segment = [Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0)];
+        let shifted = [Vector2::new(50.0, 0.0), Vector2::new(150.0, 0.0)];
+        assert!(bezier_intersections(&segment, &shifted).is_empty());
+
+        // Curves meeting end to end still touch
+        let (left, right) = rational_bezier_split_at(&weighted_points(&arch), 0.5);
+        let touch = bezier_intersections(&left, &right);
+        assert_eq!(touch.len(), 1);
+        assert!((touch[0].t1 - 1.0).abs() < 1e-3 && touch[0].t2.abs() < 1e-3);
+    }
+
+    #[test]
+    fn curve_line_intersections() {
+        let arch = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 300.0),
+            Vector2::new(200.0, 300.0),
+            Vector2::new(300.0, 0.0),
+        ];
+        let horizontal =
+            |y: f32, kind: LineKind| Line::new(Vector2::new(0.0, y), Vector2::new(100.0, y), kind);
+
+        let crossings = bezier_line_intersections(&arch, horizontal(100.0, LineKind::Line));
+        assert_eq!(crossings.len(), 2);
+        for crossing in crossings.iter() {
+            assert!((crossing.position.y - 100.0).abs() < 1e-3);
+            assert!((crossing.position.x - crossing.line_t * 100.0).abs() < 1e-3);
+        }
+        assert!((crossings[0].t + crossings[1].t - 1.0).abs() < 1e-4);
+
+        // Only the left crossing is part of the segment, none is behind the ray
+        let segment = bezier_line_intersections(&arch, horizontal(100.0, LineKind::Segment));
+        assert_eq!(segment.len(), 1);
+        assert_eq!(segment[0].t, crossings[0].t);
+        let ray = bezier_line_intersections(&arch, horizontal(100.0, LineKind::Ray));
+        assert_eq!(ray.len(), 2);
+
+        // Tangential touch at the top of the arch (y = 225) and end points on the line
+        let touch = bezier_line_intersections(&arch, horizontal(225.0, LineKind::Line));
+        assert_eq!(touch.len(), 1);
+        assert!((touch[0].t - 0.5).abs() < 1e-3);
+        let ends = bezier_line_intersections(&arch, horizontal(0.0, LineKind::Line));
+        assert_eq!(ends.iter().map(|i| i.t).collect::<Vec<_>>(), [0.0, 1.0]);
+        assert!(bezier_line_intersections(&arch, horizontal(300.0, LineKind::Line)).is_empty());
+    }
+
+    #[test]
+    fn cubic_loop_self_intersection() {
+        let looping = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(300.0, 200.0),
+            Vector2::new(-100.0, 200.0),
+            Vector2::new(200.0, 0.0),
+        ];
+        let intersections = bezier_self_intersections(&looping);
+        assert_eq!(intersections.len(), 1);
+        let intersection = intersections[0];
+        assert!(intersection.t1 < intersection.t2);
+        let first = evalute_bezier_curve(&looping, intersection.t1);
+        let second = evalute_bezier_curve(&looping, intersection.t2);
+        assert!(first.distance_to(second) < 1e-2);
+        assert!(first.distance_to(intersection.position) < 1e-2);
+        // The loop is symmetric around x = 100
+        assert!((intersection.position.x - 100.0).abs() < 1e-2);
+        assert!((intersection.t1 + intersection.t2 - 1.0).abs() < 1e-3);
+
+        let arch = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 300.0),
+            Vector2::new(200.0, 300.0),
+            Vector2::new(300.0, 0.0),
+        ];
+        assert!(bezier_self_intersections(&arch).is_empty());
+    }
+
+    #[test]
+    fn rational_curve_intersections() {
+        use super::super::rational_circle_arc;
+        use std::f32::consts::PI;
+
+        // Third of a circle of radius 100 centered on the origin, from -60° to 60°
+        let arc = rational_circle_arc(Vector2::zero(), 100.0, -PI / 3.0, 2.0 * PI / 3.0);
+        let expected = Vector2::new(86.602_54, 50.0);
+        let horizontal = [Vector2::new(0.0, 50.0), Vector2::new(200.0, 50.0)];
+        let intersections = bezier_intersections(&arc, &horizontal);
+        assert_eq!(intersections.len(), 1);
+        assert!(intersections[0].position.distance_to(expected) < 1e-2);
+        assert!(evalute_bezier_curve(&arc, intersections[0].t1).distance_to(expected) < 1e-2);
+
+        let crossings = bezier_line_intersections(
+            &arc,
+            Line::new(horizontal[0], horizontal[1], LineKind::Line),
+        );
+        assert_eq!(crossings.len(), 1);
+        assert!(crossings[0].position.distance_to(expected) < 1e-2);
+        // The vertical line x = 100 touches the arc at its middle
+        let touch = bezier_line_intersections(
+            &arc,
+            Line::new(
+                Vector2::new(100.0, 0.0),
+                Vector2::new(100.0, 100.0),
+                LineKind::Line,
+            ),
+        );
+        assert_eq!(touch.len(), 1);
+        assert!(touch[0].position.distance_to(Vector2::new(100.0, 0.0)) < 1e-2);
+
+        // A heavy middle point pulls the loop of a cubic, which still crosses itself on the curve
+        let looping = [
+            (Vector2::new(0.0, 0.0), 1.0),
+            (Vector2::new(300.0, 200.0), 2.0),
+            (Vector2::new(-100.0, 200.0), 0.5),
+            (Vector2::new(200.0, 0.0), 1.0),
+        ];
+        let intersections = bezier_self_intersections(&looping);
+        assert_eq!(intersections.len(), 1);
+        let first = evalute_bezier_curve(&looping, intersections[0].t1);
+        let second = evalute_bezier_curve(&looping, intersections[0].t2);
+        assert!(first.distance_to(second) < 1e-2);
+    }
+}
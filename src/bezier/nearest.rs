@@ -0,0 +1,155 @@
+use raylib::prelude::*;
+
+use super::{
+    evalute_bezier_curve, evalute_bezier_derivative, evalute_bezier_second_derivative, Point,
+    WeightedPoint,
+};
+
+/// Coarse samples per degree of the curve, enough to bracket every local minimum of the distance
+const SAMPLES_PER_DEGREE: usize = 8;
+const MIN_SAMPLES: usize = 16;
+const NEWTON_ITERATIONS: usize = 8;
+const NEWTON_TOLERANCE: f32 = 1e-7;
+
+/// Closest point of a curve to a given position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearestPoint {
+    pub t: f32,
+    pub position: Vector2,
+    pub distance: f32,
+}
+impl NearestPoint {
+    fn new(points: &[WeightedPoint], t: f32, position: Vector2) -> Self {
+        let curve_position = evalute_bezier_curve(points, t);
+        Self {
+            t,
+            position: curve_position,
+            distance: curve_position.distance_to(position),
+        }
+    }
+}
+
+/// Newton's method on the derivative of the squared distance, kept inside [t_min, t_max]
+fn refine(
+    points: &[WeightedPoint],
+    position: Vector2,
+    (t_min, t_max): (f32, f32),
+    mut t: f32,
+) -> f32 {
+    for _ in 0..NEWTON_ITERATIONS {
+        let offset = evalute_bezier_curve(points, t) - position;
+        let d1 = evalute_bezier_derivative(points, t);
+        let d2 = evalute_bezier_second_derivative(points, t);
+        let denominator = d1.dot(d1) + offset.dot(d2);
+        if denominator <= f32::EPSILON {
+            break;
+        }
+        let next_t = (t - offset.dot(d1) / denominator).clamp(t_min, t_max);
+        let step = (next_t - t).abs();
+        t = next_t;
+        if step < NEWTON_TOLERANCE {
+            break;
+        }
+    }
+    t
+}
+
+/// Closest point of the curve to the position
+/// The curve is sampled to find every local minimum of the distance, then each one is refined with Newton's method.
+pub fn nearest_point_on_bezier(points: &[impl Point], position: Vector2) -> NearestPoint {
+    let points = points
+        .iter()
+        .map(|p| (p.get_position(), p.get_weight()))
+        .collect::<Vec<_>>();
+    if points.len() < 2 {
+        return NearestPoint::new(&points, 0.0, position);
+    }
+
+    let sample_count = (SAMPLES_PER_DEGREE * (points.len() - 1)).max(MIN_SAMPLES);
+    let samples = (0..=sample_count)
+        .map(|i| NearestPoint::new(&points, i as f32 / sample_count as f32, position))
+        .collect::<Vec<_>>();
+
+    let mut nearest = samples[0];
+    for (i, sample) in samples.iter().enumerate() {
+        let is_local_minimum = (i == 0 || sample.distance <= samples[i - 1].distance)
+            && (i == sample_count || sample.distance <= samples[i + 1].distance);
+        if !is_local_minimum {
+            continue;
+        }
+        let bracket = (
+            samples[i.saturating_sub(1)].t,
+            samples[(i + 1).min(sample_count)].t,
+        );
+        let t = refine(&points, position, bracket, sample.t);
+        let candidate = NearestPoint::new(&points, t, position);
+        let best = if candidate.distance < sample.distance {
+            candidate
+        } else {
+            *sample
+        };
+        if best.distance < nearest.distance {
+            nearest = best;
+        }
+    }
+    nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Brute force reference
+    fn sampled_nearest(points: &[Vector2], position: Vector2) -> f32 {
+        (0..=100_000)
+            .map(|i| evalute_bezier_curve(points, i as f32 / 100_000.0).distance_to(position))
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn nearest_on_straight_line() {
+        let points = [Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0)];
+        let nearest = nearest_point_on_bezier(&points, Vector2::new(25.0, 40.0));
+        assert!((nearest.t - 0.25).abs() < 1e-5);
+        assert!(nearest.position.distance_to(Vector2::new(25.0, 0.0)) < 1e-3);
+        assert!((nearest.distance - 40.0).abs() < 1e-3);
+
+        // Beyond the end of the curve
+        let nearest = nearest_point_on_bezier(&points, Vector2::new(150.0, 10.0));
+        assert_eq!(nearest.t, 1.0);
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let cubic = [
+            Vector2::new(300.0, 600.0),
+            Vector2::new(600.0, 300.0),
+            Vector2::new(900.0, 300.0),
+            Vector2::new(1200.0, 600.0),
+        ];
+        // Loop with a self intersection and several local minimums
+        let high_degree = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(400.0, 300.0),
+            Vector2::new(-200.0, 500.0),
+            Vector2::new(100.0, -300.0),
+            Vector2::new(600.0, 200.0),
+            Vector2::new(-100.0, 400.0),
+            Vector2::new(300.0, 0.0),
+        ];
+        for points in [&cubic[..], &high_degree[..]] {
+            for position in [
+                Vector2::new(750.0, 500.0),
+                Vector2::new(100.0, 150.0),
+                Vector2::new(-50.0, 300.0),
+                Vector2::new(200.0, 200.0),
+            ] {
+                let nearest = nearest_point_on_bezier(points, position);
+                assert!(nearest.distance <= sampled_nearest(points, position) + 1e-2);
+                assert!(
+                    evalute_bezier_curve(points, nearest.t).distance_to(nearest.position) < 1e-3
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use raylib::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use super::{
+    bezier_flatten, bezier_normal, bezier_tangent, distance_to_polyline, evalute_bezier_curve,
+    fit_cubic_bezier, Point,
+};
+
+/// Offset points fitted by each cubic
+const OFFSET_SAMPLES: usize = 16;
+const OFFSET_MAX_DEPTH: u32 = 8;
+/// Longest miter relative to the stroke width (SVG default), longer ones are beveled
+const MITER_LIMIT: f32 = 4.0;
+/// Offset ends closer than this are considered attached and need no join
+const JOIN_EPSILON: f32 = 1e-3;
+
+/// Shape of the outer corner where two segments of a stroke meet at an angle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// Shape of both ends of an open stroke
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: StrokeJoin,
+    pub cap: StrokeCap,
+}
+
+fn offset_point(points: &[Vector2], t: f32, distance: f32) -> Vector2 {
+    evalute_bezier_curve(points, t) + bezier_normal(points, t) * distance
+}
+
+fn offset_in(
+    points: &[Vector2],
+    (t0, t1): (f32, f32),
+    distance: f32,
+    tolerance: f32,
+    depth: u32,
+    cubics: &mut Vec<[Vector2; 4]>,
+) {
+    let step = (t1 - t0) / OFFSET_SAMPLES as f32;
+    let samples = (0..=OFFSET_SAMPLES)
+        .map(|i| offset_point(points, t0 + i as f32 * step, distance))
+        .collect::<Vec<_>>();
+    let fitted = fit_cubic_bezier(
+        &samples,
+        bezier_tangent(points, t0),
+        bezier_tangent(points, t1),
+    );
+    let fitted_polyline = bezier_flatten(&fitted, tolerance * 0.1)
+        .into_iter()
+        .map(|p| p.position)
+        .collect::<Vec<_>>();
+    // The offset is also checked between the fitted samples
+    let error = (0..OFFSET_SAMPLES)
+        .map(|i| offset_point(points, t0 + (i as f32 + 0.5) * step, distance))
+        .chain(samples.iter().copied())
+        .map(|sample| distance_to_polyline(sample, &fitted_polyline))
+        .fold(0.0, f32::max);
+
+    if error <= tolerance || depth == 0 {
+        cubics.push(fitted);
+    } else {
+        let middle = (t0 + t1) * 0.5;
+        offset_in(points, (t0, middle), distance, tolerance, depth - 1, cubics);
+        offset_in(points, (middle, t1), distance, tolerance, depth - 1, cubics);
+    }
+}
+
+/// Offset of a curve of any degree approximated with cubic Bezier within the tolerance
+/// Positive distances are on the side of the normal. Loops appearing where the distance is larger than the
+/// radius of curvature are kept.
+pub fn bezier_offset(points: &[impl Point], distance: f32, tolerance: f32) -> Vec<[Vector2; 4]> {
+    let points = points.iter().map(|p| p.get_position()).collect::<Vec<_>>();
+    let mut cubics = Vec::new();
+    if points.len() > 1 {
+        offset_in(
+            &points,
+            (0.0, 1.0),
+            distance,
+            tolerance,
+            OFFSET_MAX_DEPTH,
+            &mut cubics,
+        );
+    }
+    cubics
+}
+
+fn line_cubic(from: Vector2, to: Vector2) -> [Vector2; 4] {
+    [from, from.lerp(to, 1.0 / 3.0), from.lerp(to, 2.0 / 3.0), to]
+}
+
+/// Circular arc around the center starting at `from`, counterclockwise for positive sweeps
+fn arc_cubics(center: Vector2, from: Vector2, sweep: f32, cubics: &mut Vec<[Vector2; 4]>) {
+    let pieces = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let angle = sweep / pieces as f32;
+    let handle_length = 4.0 / 3.0 * (angle / 4.0).tan();
+    let mut start = from - center;
+    for _ in 0..pieces {
+        let end = start.rotated(angle);
+        cubics.push([
+            center + start,
+            center + start + start.rotated(FRAC_PI_2) * handle_length,
+            center + end - end.rotated(FRAC_PI_2) * handle_length,
+            center + end,
+        ]);
+        start = end;
+    }
+}
+
+/// Connect the offsets of two attached segments around their join point
+fn stroke_join(
+    segment: &[Vector2; 4],
+    next_segment: &[Vector2; 4],
+    distance: f32,
+    style: StrokeJoin,
+    cubics: &mut Vec<[Vector2; 4]>,
+) {
+    let join = segment[3];
+    let from = offset_point(segment, 1.0, distance);
+    let to = offset_point(next_segment, 0.0, distance);
+    let incoming = bezier_tangent(segment, 1.0);
+    let outgoing = bezier_tangent(next_segment, 0.0);
+    if from.distance_to(to) < JOIN_EPSILON {
+        return;
+    }
+    // The offsets overlap on the inner side of the turn, a straight line keeps the outline attached
+    let turn = incoming.x * outgoing.y - incoming.y * outgoing.x;
+    if turn * distance >= 0.0 {
+        cubics.push(line_cubic(from, to));
+        return;
+    }
+    let (start, end) = (from - join, to - join);
+    match style {
+        StrokeJoin::Bevel => cubics.push(line_cubic(from, to)),
+        StrokeJoin::Round => {
+            let sweep = (start.x * end.y - start.y * end.x).atan2(start.dot(end));
+            arc_cubics(join, from, sweep, cubics);
+        }
+        StrokeJoin::Miter => {
+            let bisector = start + end;
+            let cos_half_angle = if bisector.length() > JOIN_EPSILON {
+                bisector.normalized().dot(start.normalized())
+            } else {
+                0.0
+            };
+            if cos_half_angle * MITER_LIMIT < 1.0 {
+                cubics.push(line_cubic(from, to));
+            } else {
+                let tip = join + bisector.normalized() * distance.abs() / cos_half_angle;
+                cubics.push(line_cubic(from, tip));
+                cubics.push(line_cubic(tip, to));
+            }
+        }
+    }
+}
+
+/// Cap going from the side of the normal to the other side around the end of the stroke
+fn stroke_cap(
+    end: Vector2,
+    direction: Vector2,
+    distance: f32,
+    style: StrokeCap,
+    cubics: &mut Vec<[Vector2; 4]>,
+) {
+    let offset = Vector2::new(-direction.y, direction.x) * distance;
+    let (from, to) = (end + offset, end - offset);
+    match style {
+        StrokeCap::Butt => cubics.push(line_cubic(from, to)),
+        StrokeCap::Square => {
+            let extension = direction * distance;
+            cubics.push(line_cubic(from, from + extension));
+            cubics.push(line_cubic(from + extension, to + extension));
+            cubics.push(line_cubic(to + extension, to));
+        }
+        StrokeCap::Round => {
+            let sweep = if offset.rotated(FRAC_PI_2).dot(direction) > 0.0 {
+                PI
+            } else {
+                -PI
+            };
+            arc_cubics(end, from, sweep, cubics);
+        }
+    }
+}
+
+/// Offset of a whole chain on one side, with joins between its segments
+fn stroke_side(
+    segments: &[[Vector2; 4]],
+    is_closed: bool,
+    distance: f32,
+    style: &StrokeStyle,
+    tolerance: f32,
+    cubics: &mut Vec<[Vector2; 4]>,
+) {
+    for (i, segment) in segments.iter().enumerate() {
+        cubics.extend(bezier_offset(segment, distance, tolerance));
+        let next_segment = if i + 1 < segments.len() {
+            segments[i + 1]
+        } else if is_closed {
+            segments[0]
+        } else {
+            continue;
+        };
+        stroke_join(segment, &next_segment, distance, style.join, cubics);
+    }
+}
+
+/// Outline of the stroke of a chain of cubic Bezier, as closed chains of cubic Bezier
+/// An open chain gives a single contour going along one side, around the end cap, back along the other side and
+/// around the start cap. A closed chain gives one contour on each side.
+pub fn bezier_stroke_outline(
+    segments: &[[Vector2; 4]],
+    is_closed: bool,
+    style: &StrokeStyle,
+    tolerance: f32,
+) -> Vec<Vec<[Vector2; 4]>> {
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return Vec::new();
+    };
+    let distance = style.width * 0.5;
+    // Going backward swaps the sides, so the other side is an offset of the reversed chain
+    let reversed = segments
+        .iter()
+        .rev()
+        .map(|s| [s[3], s[2], s[1], s[0]])
+        .collect::<Vec<_>>();
+    let mut contour = Vec::new();
+    stroke_side(
+        segments,
+        is_closed,
+        distance,
+        style,
+        tolerance,
+        &mut contour,
+    );
+    let mut other_side = Vec::new();
+    stroke_side(
+        &reversed,
+        is_closed,
+        distance,
+        style,
+        tolerance,
+        &mut other_side,
+    );
+    if is_closed {
+        return vec![contour, other_side];
+    }
+
+    stroke_cap(
+        last[3],
+        bezier_tangent(last, 1.0),
+        distance,
+        style.cap,
+        &mut contour,
+    );
+    contour.extend(other_side);
+    stroke_cap(
+        first[0],
+        -bezier_tangent(first, 0.0),
+        distance,
+        style.cap,
+        &mut contour,
+    );
+    vec![contour]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contour_points(contour: &[[Vector2; 4]]) -> Vec<Vector2> {
+        contour
+            .iter()
+            .flat_map(|cubic| (0..=10).map(move |i| evalute_bezier_curve(cubic, i as f32 / 10.0)))
+            .collect()
+    }
+
+    fn assert_closed(contour: &[[Vector2; 4]]) {
+        for pair in contour.windows(2) {
+            assert!(pair[0][3].distance_to(pair[1][0]) < 1e-2);
+        }
+        assert!(contour[contour.len() - 1][3].distance_to(contour[0][0]) < 1e-2);
+    }
+
+    #[test]
+    fn offset_stays_at_distance() {
+        let line = [Vector2::new(0.0, 0.0), Vector2::new(300.0, 0.0)];
+        for point in contour_points(&bezier_offset(&line, 20.0, 0.1)) {
+            assert!((point.y - 20.0).abs() < 1e-3);
+        }
+
+        // Quarter of a circle of radius 100 centered on the origin
+        let k = 100.0 * 0.552_284_8;
+        let arc = [
+            Vector2::new(100.0, 0.0),
+            Vector2::new(100.0, k),
+            Vector2::new(k, 100.0),
+            Vector2::new(0.0, 100.0),
+        ];
+        for (distance, radius) in [(10.0, 90.0), (-10.0, 110.0)] {
+            for point in contour_points(&bezier_offset(&arc, distance, 0.1)) {
+                assert!((point.length() - radius).abs() < 0.2);
+            }
+        }
+    }
+
+    #[test]
+    fn caps_of_a_straight_stroke() {
+        let segments = [[
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 0.0),
+            Vector2::new(200.0, 0.0),
+            Vector2::new(300.0, 0.0),
+        ]];
+        for (cap, min_x, max_x) in [
+            (StrokeCap::Butt, 0.0, 300.0),
+            (StrokeCap::Square, -10.0, 310.0),
+            (StrokeCap::Round, -10.0, 310.0),
+        ] {
+            let style = StrokeStyle {
+                width: 20.0,
+                join: StrokeJoin::Miter,
+                cap,
+            };
+            let outline = bezier_stroke_outline(&segments, false, &style, 0.1);
+            assert_eq!(outline.len(), 1);
+            assert_closed(&outline[0]);
+            let points = contour_points(&outline[0]);
+            let x = points.iter().map(|p| p.x);
+            assert!((x.clone().fold(f32::INFINITY, f32::min) - min_x).abs() < 1e-2);
+            assert!((x.fold(f32::NEG_INFINITY, f32::max) - max_x).abs() < 1e-2);
+            for point in points {
+                assert!(point.y.abs() < 10.0 + 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn joins_of_a_right_angle() {
+        let corner = Vector2::new(100.0, 0.0);
+        let segments = [
+            [
+                Vector2::new(0.0, 0.0),
+                Vector2::new(30.0, 0.0),
+                Vector2::new(60.0, 0.0),
+                corner,
+            ],
+            [
+                corner,
+                Vector2::new(100.0, 30.0),
+                Vector2::new(100.0, 60.0),
+                Vector2::new(100.0, 100.0),
+            ],
+        ];
+        let outer_corner = corner + Vector2::new(10.0, -10.0);
+        let outline = |join| {
+            let style = StrokeStyle {
+                width: 20.0,
+                join,
+                cap: StrokeCap::Butt,
+            };
+            let outline = bezier_stroke_outline(&segments, false, &style, 0.1);
+            assert_closed(&outline[0]);
+            contour_points(&outline[0])
+        };
+        let closest_to_outer_corner = |points: &[Vector2]| {
+            points
+                .iter()
+                .map(|p| p.distance_to(outer_corner))
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        assert!(closest_to_outer_corner(&outline(StrokeJoin::Miter)) < 1e-2);
+        // The bevel cuts the corner at its middle, the round join stays at the stroke distance
+        let bevel = closest_to_outer_corner(&outline(StrokeJoin::Bevel));
+        assert!((bevel - 10.0 / 2f32.sqrt()).abs() < 1e-2);
+        let round = outline(StrokeJoin::Round);
+        assert!((closest_to_outer_corner(&round) - (10.0 * 2f32.sqrt() - 10.0)).abs() < 1e-2);
+        for point in round {
+            assert!(point.x <= 110.0 + 1e-2 && point.y >= -10.0 - 1e-2);
+        }
+    }
+}
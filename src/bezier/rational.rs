@@ -0,0 +1,197 @@
+use raylib::prelude::*;
+
+use super::{Point, WeightedPoint};
+
+/// Control point in homogeneous coordinates (position multiplied by the weight, and the weight)
+/// A rational curve is the projection of the polynomial curve of its homogeneous control points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HomogeneousPoint {
+    pub weighted_position: Vector2,
+    pub weight: f32,
+}
+impl HomogeneousPoint {
+    pub fn new(position: Vector2, weight: f32) -> Self {
+        Self {
+            weighted_position: position * weight,
+            weight,
+        }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            weighted_position: self.weighted_position.lerp(other.weighted_position, t),
+            weight: self.weight + (other.weight - self.weight) * t,
+        }
+    }
+
+    /// Position of the point back in the plane
+    pub fn position(&self) -> Vector2 {
+        self.weighted_position * (1.0 / self.weight)
+    }
+}
+
+pub fn homogeneous_points(points: &[impl Point]) -> Vec<HomogeneousPoint> {
+    points
+        .iter()
+        .map(|p| HomogeneousPoint::new(p.get_position(), p.get_weight()))
+        .collect()
+}
+
+/// Whether some weight differs from 1 (curves with equal weights are plain polynomial curves)
+pub fn is_rational(points: &[impl Point]) -> bool {
+    points.iter().any(|p| p.get_weight() != 1.0)
+}
+
+/// Every level of De Casteljau's algorithm in homogeneous coordinates
+pub fn homogeneous_levels(points: &[impl Point], t: f32) -> Vec<Vec<HomogeneousPoint>> {
+    let mut levels = vec![homogeneous_points(points)];
+    while levels[levels.len() - 1].len() > 1 {
+        let next_points = levels[levels.len() - 1]
+            .windows(2)
+            .map(|w| w[0].lerp(&w[1], t))
+            .collect::<Vec<_>>();
+        levels.push(next_points);
+    }
+    levels
+}
+
+/// Control points of the derivative of the homogeneous curve (the weights become derivatives too)
+fn homogeneous_derivative(points: &[HomogeneousPoint]) -> Vec<HomogeneousPoint> {
+    let n = points.len() as f32 - 1.0;
+    points
+        .windows(2)
+        .map(|w| HomogeneousPoint {
+            weighted_position: (w[1].weighted_position - w[0].weighted_position) * n,
+            weight: (w[1].weight - w[0].weight) * n,
+        })
+        .collect()
+}
+
+fn evaluate_homogeneous(points: &[HomogeneousPoint], t: f32) -> HomogeneousPoint {
+    if points.is_empty() {
+        return HomogeneousPoint {
+            weighted_position: Vector2::zero(),
+            weight: 0.0,
+        };
+    }
+    let mut lerp_points = points.to_vec();
+    let n = lerp_points.len();
+    for level in 1..n {
+        for i in 0..n - level {
+            lerp_points[i] = lerp_points[i].lerp(&lerp_points[i + 1], t);
+        }
+    }
+    lerp_points[0]
+}
+
+/// Evaluate a point on a rational curve
+pub fn evaluate_rational_bezier(points: &[impl Point], t: f32) -> Vector2 {
+    evaluate_homogeneous(&homogeneous_points(points), t).position()
+}
+
+/// First and second derivatives of a rational curve (quotient rule on the homogeneous curve)
+pub fn rational_derivatives(points: &[impl Point], t: f32) -> (Vector2, Vector2) {
+    let points = homogeneous_points(points);
+    let derivative = homogeneous_derivative(&points);
+    let second_derivative = homogeneous_derivative(&derivative);
+    let a = evaluate_homogeneous(&points, t);
+    let d1 = evaluate_homogeneous(&derivative, t);
+    let d2 = evaluate_homogeneous(&second_derivative, t);
+
+    let position = a.position();
+    let first = (d1.weighted_position - position * d1.weight) * (1.0 / a.weight);
+    let second = (d2.weighted_position - first * (2.0 * d1.weight) - position * d2.weight)
+        * (1.0 / a.weight);
+    (first, second)
+}
+
+/// Exact circle arc as a rational quadratic curve (sweeps below 180°)
+/// The middle control point is where the tangents at both ends meet, with the cosine of half the sweep as weight.
+pub fn rational_circle_arc(
+    center: Vector2,
+    radius: f32,
+    start_angle: f32,
+    sweep: f32,
+) -> [WeightedPoint; 3] {
+    let half_sweep = sweep * 0.5;
+    let direction = |angle: f32| Vector2::new(angle.cos(), angle.sin());
+    [
+        (center + direction(start_angle) * radius, 1.0),
+        (
+            center + direction(start_angle + half_sweep) * (radius / half_sweep.cos()),
+            half_sweep.cos(),
+        ),
+        (center + direction(start_angle + sweep) * radius, 1.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        bezier_tangent, evalute_bezier_curve, evalute_bezier_derivative, rational_bezier_split_at,
+        ArcLengthTable,
+    };
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn circle_arc_is_exact() {
+        let center = Vector2::new(100.0, 50.0);
+        let arc = rational_circle_arc(center, 200.0, 0.3, 2.0 * PI / 3.0);
+        assert!(is_rational(&arc));
+        for i in 0..=50 {
+            let t = i as f32 / 50.0;
+            let position = evalute_bezier_curve(&arc, t);
+            assert!((position.distance_to(center) - 200.0).abs() < 1e-2);
+            // The tangent of a circle is perpendicular to the radius
+            assert!(
+                bezier_tangent(&arc, t)
+                    .dot((position - center).normalized())
+                    .abs()
+                    < 1e-3
+            );
+        }
+        assert!((ArcLengthTable::from_curve(&arc).length() - 200.0 * 2.0 * PI / 3.0).abs() < 1e-2);
+
+        // Both halves of a split arc are still exact arcs
+        let (left, right) = rational_bezier_split_at(&arc, 0.3);
+        for half in [left, right] {
+            for i in 0..=10 {
+                let position = evalute_bezier_curve(&half, i as f32 / 10.0);
+                assert!((position.distance_to(center) - 200.0).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        let points = [
+            (Vector2::new(0.0, 0.0), 1.0),
+            (Vector2::new(100.0, 300.0), 3.0),
+            (Vector2::new(300.0, -100.0), 0.5),
+            (Vector2::new(400.0, 100.0), 1.0),
+        ];
+        const H: f32 = 1e-3;
+        for i in 1..10 {
+            let t = i as f32 / 10.0;
+            let difference = (evalute_bezier_curve(&points, t + H)
+                - evalute_bezier_curve(&points, t - H))
+                * (0.5 / H);
+            let derivative = evalute_bezier_derivative(&points, t);
+            assert!(derivative.distance_to(difference) < 1e-2 * derivative.length());
+        }
+
+        // Equal weights do not change the curve
+        let same_weights = points.map(|(position, _)| (position, 2.0));
+        let polynomial = points.map(|(position, _)| position);
+        assert!(!is_rational(&polynomial));
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!(
+                evalute_bezier_curve(&same_weights, t)
+                    .distance_to(evalute_bezier_curve(&polynomial, t))
+                    < 1e-3
+            );
+        }
+    }
+}
@@ -0,0 +1,883 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::positions;
+use super::{
+    bezier_curvature, bezier_intersections, bezier_line_intersections, bezier_normal,
+    bezier_self_intersections, bezier_split_at, bezier_stroke_outline, bezier_tangent,
+    evalute_bezier_curve, fit_cubic_bezier, nearest_point_on_bezier, ArcLengthTable, BasicPoint,
+    Intersection, Line, LineIntersection, NearestPoint, StrokeStyle, SAMPLES,
+};
+use crate::colors::*;
+
+/// How the two handles around a join point constrain each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JoinMode {
+    /// Handles move freely
+    Corner,
+    /// Handles stay collinear with the join but keep their own length
+    Smooth,
+    /// Handles are mirrored around the join
+    Symmetric,
+}
+impl JoinMode {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Corner => Self::Smooth,
+            Self::Smooth => Self::Symmetric,
+            Self::Symmetric => Self::Corner,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplinePointKind {
+    Join,
+    PreviousHandle,
+    NextHandle,
+}
+
+/// Index based reference to one point of a spline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplinePointId {
+    pub join: usize,
+    pub kind: SplinePointKind,
+}
+impl SplinePointId {
+    pub fn new(join: usize, kind: SplinePointKind) -> Self {
+        Self { join, kind }
+    }
+}
+
+/// A join point of the spline with the handles of the segments before and after it
+#[derive(Debug, Clone, Copy)]
+pub struct SplineJoin {
+    pub join: BasicPoint,
+    pub previous_handle: BasicPoint,
+    pub next_handle: BasicPoint,
+    pub mode: JoinMode,
+}
+impl SplineJoin {
+    pub fn new(
+        position: Vector2,
+        previous_handle: Vector2,
+        next_handle: Vector2,
+        mode: JoinMode,
+    ) -> Self {
+        Self {
+            join: BasicPoint::new(position, COLOR_BLUE),
+            previous_handle: BasicPoint::new(previous_handle, COLOR_LIGHT),
+            next_handle: BasicPoint::new(next_handle, COLOR_LIGHT),
+            mode,
+        }
+    }
+
+    pub fn point(&self, kind: SplinePointKind) -> &BasicPoint {
+        match kind {
+            SplinePointKind::Join => &self.join,
+            SplinePointKind::PreviousHandle => &self.previous_handle,
+            SplinePointKind::NextHandle => &self.next_handle,
+        }
+    }
+
+    pub fn point_mut(&mut self, kind: SplinePointKind) -> &mut BasicPoint {
+        match kind {
+            SplinePointKind::Join => &mut self.join,
+            SplinePointKind::PreviousHandle => &mut self.previous_handle,
+            SplinePointKind::NextHandle => &mut self.next_handle,
+        }
+    }
+}
+
+/// Mode matching the current handles of a join
+fn guess_join_mode(join: Vector2, previous_handle: Vector2, next_handle: Vector2) -> JoinMode {
+    const EPSILON: f32 = 1e-2;
+    let previous = previous_handle - join;
+    let next = next_handle - join;
+    let scale = previous.length().max(next.length()).max(1.0);
+    if (previous + next).length() < EPSILON * scale {
+        JoinMode::Symmetric
+    } else if (previous.x * next.y - previous.y * next.x).abs()
+        < EPSILON * previous.length() * next.length()
+        && previous.dot(next) < 0.0
+    {
+        JoinMode::Smooth
+    } else {
+        JoinMode::Corner
+    }
+}
+
+/// Piecewise cubic Bezier spline
+/// Segment i goes from join i to join i + 1 through the next handle of join i and the previous handle of join i + 1.
+/// When the spline is a closed loop, the last segment goes from the last join back to the first one.
+#[derive(Debug, Clone)]
+pub struct Spline {
+    joins: Vec<SplineJoin>,
+    is_closed_loop: bool,
+}
+impl Spline {
+    /// Create a spline made of a single cubic Bezier
+    pub fn new(segment: [Vector2; 4]) -> Self {
+        Self {
+            joins: vec![
+                SplineJoin::new(
+                    segment[0],
+                    segment[0] * 2.0 - segment[1],
+                    segment[1],
+                    JoinMode::Symmetric,
+                ),
+                SplineJoin::new(
+                    segment[3],
+                    segment[2],
+                    segment[3] * 2.0 - segment[2],
+                    JoinMode::Symmetric,
+                ),
+            ],
+            is_closed_loop: false,
+        }
+    }
+
+    /// Create a spline from existing joins (at least two joins are needed)
+    pub fn from_joins(joins: Vec<SplineJoin>, is_closed_loop: bool) -> Option<Self> {
+        if joins.len() < 2 {
+            return None;
+        }
+        Some(Self {
+            joins,
+            is_closed_loop,
+        })
+    }
+
+    /// Create a spline from a chain of attached cubic Bezier
+    /// When closed, the last cubic must end where the first one starts. Join modes are guessed from the handles.
+    pub fn from_cubics(cubics: &[[Vector2; 4]], is_closed_loop: bool) -> Option<Self> {
+        if cubics.is_empty() {
+            return None;
+        }
+        if is_closed_loop && cubics.len() == 1 {
+            // A loop needs at least two joins
+            let (left, right) = bezier_split_at(&cubics[0], 0.5);
+            return Self::from_cubics(
+                &[
+                    [left[0], left[1], left[2], left[3]],
+                    [right[0], right[1], right[2], right[3]],
+                ],
+                true,
+            );
+        }
+
+        let mut joins = Vec::with_capacity(cubics.len() + 1);
+        for (i, cubic) in cubics.iter().enumerate() {
+            let previous_handle = if i > 0 {
+                cubics[i - 1][2]
+            } else if is_closed_loop {
+                cubics[cubics.len() - 1][2]
+            } else {
+                cubic[0] * 2.0 - cubic[1]
+            };
+            joins.push(SplineJoin::new(
+                cubic[0],
+                previous_handle,
+                cubic[1],
+                guess_join_mode(cubic[0], previous_handle, cubic[1]),
+            ));
+        }
+        if !is_closed_loop {
+            let last = cubics[cubics.len() - 1];
+            joins.push(SplineJoin::new(
+                last[3],
+                last[2],
+                last[3] * 2.0 - last[2],
+                JoinMode::Symmetric,
+            ));
+        }
+        Self::from_joins(joins, is_closed_loop)
+    }
+
+    pub fn joins(&self) -> &[SplineJoin] {
+        &self.joins
+    }
+
+    pub fn is_closed_loop(&self) -> bool {
+        self.is_closed_loop
+    }
+
+    /// Number of cubic segments in the spline (including the closing one)
+    pub fn segment_count(&self) -> usize {
+        if self.joins.len() < 2 {
+            0
+        } else if self.is_closed_loop {
+            self.joins.len()
+        } else {
+            self.joins.len() - 1
+        }
+    }
+
+    /// Control points of one cubic segment of the spline
+    pub fn segment(&self, segment: usize) -> Option<[BasicPoint; 4]> {
+        if segment >= self.segment_count() {
+            return None;
+        }
+        let start = &self.joins[segment];
+        let end = &self.joins[(segment + 1) % self.joins.len()];
+        Some([start.join, start.next_handle, end.previous_handle, end.join])
+    }
+
+    /// Control points of every cubic segment of the spline
+    pub fn segments(&self) -> impl Iterator<Item = [BasicPoint; 4]> + '_ {
+        (0..self.segment_count()).filter_map(|segment| self.segment(segment))
+    }
+
+    /// Whether the handle before the join is part of a segment
+    pub fn has_previous_handle(&self, join: usize) -> bool {
+        self.is_closed_loop || join > 0
+    }
+
+    /// Whether the handle after the join is part of a segment
+    pub fn has_next_handle(&self, join: usize) -> bool {
+        self.is_closed_loop || join + 1 < self.joins.len()
+    }
+
+    /// Every point of the spline that is part of a segment
+    pub fn point_ids(&self) -> impl Iterator<Item = SplinePointId> {
+        let len = self.joins.len();
+        let is_closed_loop = self.is_closed_loop;
+        (0..len).flat_map(move |join| {
+            [
+                (is_closed_loop || join > 0)
+                    .then_some(SplinePointId::new(join, SplinePointKind::PreviousHandle)),
+                Some(SplinePointId::new(join, SplinePointKind::Join)),
+                (is_closed_loop || join + 1 < len)
+                    .then_some(SplinePointId::new(join, SplinePointKind::NextHandle)),
+            ]
+            .into_iter()
+            .flatten()
+        })
+    }
+
+    pub fn point(&self, id: SplinePointId) -> &BasicPoint {
+        self.joins[id.join].point(id.kind)
+    }
+
+    pub fn point_mut(&mut self, id: SplinePointId) -> &mut BasicPoint {
+        self.joins[id.join].point_mut(id.kind)
+    }
+
+    /// Move one point of the spline
+    /// With constraint, a join carries its handles along and a handle drags the opposite one according to the join mode.
+    pub fn move_point(&mut self, id: SplinePointId, position: Vector2, with_constraint: bool) {
+        let has_previous_handle = self.has_previous_handle(id.join);
+        let has_next_handle = self.has_next_handle(id.join);
+        let join = &mut self.joins[id.join];
+        match id.kind {
+            SplinePointKind::Join => {
+                let movement = position - join.join.position;
+                join.join.position = position;
+                if with_constraint {
+                    join.previous_handle.position += movement;
+                    join.next_handle.position += movement;
+                }
+            }
+            SplinePointKind::PreviousHandle | SplinePointKind::NextHandle => {
+                join.point_mut(id.kind).position = position;
+                if !with_constraint || !has_previous_handle || !has_next_handle {
+                    return;
+                }
+                let join_position = join.join.position;
+                let opposite = if id.kind == SplinePointKind::PreviousHandle {
+                    &mut join.next_handle
+                } else {
+                    &mut join.previous_handle
+                };
+                match join.mode {
+                    JoinMode::Corner => {}
+                    JoinMode::Smooth => {
+                        let direction = join_position - position;
+                        if direction.length() > f32::EPSILON {
+                            let opposite_length = opposite.position.distance_to(join_position);
+                            opposite.position =
+                                join_position + direction.normalized() * opposite_length;
+                        }
+                    }
+                    JoinMode::Symmetric => {
+                        opposite.position = join_position * 2.0 - position;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move a group of points to the transformed positions they have in the original spline
+    /// With constraint, the handles of a transformed join are transformed along and a transformed handle drags the opposite one (if not transformed) according to the join mode.
+    pub fn transform_points(
+        &mut self,
+        original: &Spline,
+        ids: &[SplinePointId],
+        transform: impl Fn(Vector2) -> Vector2,
+        with_constraint: bool,
+    ) {
+        let mut transformed_ids = ids.to_vec();
+        if with_constraint {
+            for id in ids.iter().filter(|id| id.kind == SplinePointKind::Join) {
+                for kind in [SplinePointKind::PreviousHandle, SplinePointKind::NextHandle] {
+                    let handle = SplinePointId::new(id.join, kind);
+                    if !transformed_ids.contains(&handle) {
+                        transformed_ids.push(handle);
+                    }
+                }
+            }
+        }
+        for id in transformed_ids.iter() {
+            self.point_mut(*id).position = transform(original.point(*id).position);
+        }
+        if !with_constraint {
+            return;
+        }
+        for id in ids {
+            let opposite_kind = match id.kind {
+                SplinePointKind::Join => continue,
+                SplinePointKind::PreviousHandle => SplinePointKind::NextHandle,
+                SplinePointKind::NextHandle => SplinePointKind::PreviousHandle,
+            };
+            if !transformed_ids.contains(&SplinePointId::new(id.join, opposite_kind)) {
+                self.move_point(*id, self.point(*id).position, true);
+            }
+        }
+    }
+
+    /// Change the mode of a join and apply its constraint right away
+    pub fn set_join_mode(&mut self, join: usize, mode: JoinMode) {
+        self.joins[join].mode = mode;
+        let previous_handle = SplinePointId::new(join, SplinePointKind::PreviousHandle);
+        self.move_point(previous_handle, self.point(previous_handle).position, true);
+    }
+
+    /// Attach a new cubic Bezier at the end of the spline (not possible on a closed loop)
+    pub fn push_join(&mut self, position: Vector2) -> bool {
+        if self.is_closed_loop {
+            return false;
+        }
+        let last = self.joins.last_mut().unwrap();
+        // New handle is mirrored with the previous handle of the last join
+        last.next_handle.position = last.join.position * 2.0 - last.previous_handle.position;
+        let previous_handle = (position + last.join.position) * 0.5;
+        self.joins.push(SplineJoin::new(
+            position,
+            previous_handle,
+            position * 2.0 - previous_handle,
+            JoinMode::Symmetric,
+        ));
+        true
+    }
+
+    /// Remove the last cubic Bezier of the spline, or reopen the loop if closed
+    pub fn pop_segment(&mut self) -> bool {
+        if self.is_closed_loop {
+            self.is_closed_loop = false;
+            true
+        } else if self.joins.len() > 2 {
+            self.joins.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Attach the end and the start of the spline with a new cubic Bezier
+    pub fn close_loop(&mut self) -> bool {
+        if self.is_closed_loop || self.joins.len() < 2 {
+            return false;
+        }
+        let last = self.joins.last_mut().unwrap();
+        last.next_handle.position = last.join.position * 2.0 - last.previous_handle.position;
+        let first = &mut self.joins[0];
+        first.previous_handle.position = first.join.position * 2.0 - first.next_handle.position;
+        self.is_closed_loop = true;
+        true
+    }
+
+    /// Split one segment at t into two cubic Bezier joined by a new join
+    /// The shape of the spline is left untouched.
+    pub fn insert_join(&mut self, segment: usize, t: f32) -> Option<usize> {
+        let points = self.segment(segment)?;
+        let (left, right) = bezier_split_at(&positions(&points), t);
+        let next_join = (segment + 1) % self.joins.len();
+        self.joins[segment].next_handle.position = left[1];
+        self.joins[next_join].previous_handle.position = right[2];
+        // Split handles are collinear but not of the same length
+        self.joins.insert(
+            segment + 1,
+            SplineJoin::new(left[3], left[2], right[1], JoinMode::Smooth),
+        );
+        Some(segment + 1)
+    }
+
+    /// Remove a join, merging its two adjacent segments into a single cubic Bezier
+    /// At the ends of an open spline, the end segment is removed instead.
+    pub fn remove_join(&mut self, join: usize) -> bool {
+        // Keep at least one segment (two joins)
+        if join >= self.joins.len() || self.joins.len() <= 2 {
+            return false;
+        }
+        if !self.is_closed_loop && (join == 0 || join == self.joins.len() - 1) {
+            self.joins.remove(join);
+            return true;
+        }
+
+        let len = self.joins.len();
+        let previous_join = (join + len - 1) % len;
+        let next_join = (join + 1) % len;
+        let previous_segment = self.segment(previous_join).unwrap();
+        let next_segment = self.segment(join).unwrap();
+        let samples = (0..=SAMPLES)
+            .map(|i| evalute_bezier_curve(&previous_segment, i as f32 / SAMPLES as f32))
+            .chain(
+                (1..=SAMPLES)
+                    .map(|i| evalute_bezier_curve(&next_segment, i as f32 / SAMPLES as f32)),
+            )
+            .collect::<Vec<_>>();
+        let merged = fit_cubic_bezier(
+            &samples,
+            bezier_tangent(&previous_segment, 0.0),
+            bezier_tangent(&next_segment, 1.0),
+        );
+        self.joins[previous_join].next_handle.position = merged[1];
+        self.joins[next_join].previous_handle.position = merged[2];
+        self.joins.remove(join);
+        true
+    }
+
+    /// Arc length lookup table over every segment of the spline
+    pub fn arc_length_table(&self) -> ArcLengthTable {
+        ArcLengthTable::new(&self.segments().collect::<Vec<_>>())
+    }
+
+    /// Segment and closest point of the spline to the position
+    pub fn nearest_point(&self, position: Vector2) -> Option<(usize, NearestPoint)> {
+        self.segments()
+            .map(|points| nearest_point_on_bezier(&points, position))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Crossing points between the segments of two splines (segment of this spline, segment of the other one, intersection)
+    pub fn intersections(&self, other: &Spline) -> Vec<(usize, usize, Intersection)> {
+        const POSITION_TOLERANCE: f32 = 1e-2;
+        let mut intersections: Vec<(usize, usize, Intersection)> = Vec::new();
+        for (segment, points) in self.segments().enumerate() {
+            for (other_segment, other_points) in other.segments().enumerate() {
+                for intersection in bezier_intersections(&points, &other_points) {
+                    // Crossing points on a join are found on the segments on both sides
+                    let is_known = intersections.iter().any(|(_, _, known)| {
+                        known.position.distance_to(intersection.position) < POSITION_TOLERANCE
+                    });
+                    if !is_known {
+                        intersections.push((segment, other_segment, intersection));
+                    }
+                }
+            }
+        }
+        intersections
+    }
+
+    /// Points where the spline crosses itself (first segment, second segment, intersection)
+    /// Loops of a single segment are reported with the same segment twice. Joins shared by consecutive segments are not crossings.
+    pub fn self_intersections(&self) -> Vec<(usize, usize, Intersection)> {
+        const END_TOLERANCE: f32 = 1e-3;
+        let segments = self.segments().collect::<Vec<_>>();
+        let segment_count = segments.len();
+        let follows = |a: usize, b: usize| {
+            b == a + 1 || (self.is_closed_loop && a + 1 == segment_count && b == 0)
+        };
+        let mut intersections = Vec::new();
+        for (segment, points) in segments.iter().enumerate() {
+            for intersection in bezier_self_intersections(points) {
+                intersections.push((segment, segment, intersection));
+            }
+            for (other_segment, other_points) in segments.iter().enumerate().skip(segment + 1) {
+                for intersection in bezier_intersections(points, other_points) {
+                    let is_shared_join = (follows(segment, other_segment)
+                        && intersection.t1 > 1.0 - END_TOLERANCE
+                        && intersection.t2 < END_TOLERANCE)
+                        || (follows(other_segment, segment)
+                            && intersection.t1 < END_TOLERANCE
+                            && intersection.t2 > 1.0 - END_TOLERANCE);
+                    if !is_shared_join {
+                        intersections.push((segment, other_segment, intersection));
+                    }
+                }
+            }
+        }
+        intersections
+    }
+
+    /// Points where the segments of the spline cross or touch a line (segment, intersection)
+    pub fn line_intersections(&self, line: Line) -> Vec<(usize, LineIntersection)> {
+        const POSITION_TOLERANCE: f32 = 1e-2;
+        let mut intersections: Vec<(usize, LineIntersection)> = Vec::new();
+        for (segment, points) in self.segments().enumerate() {
+            for intersection in bezier_line_intersections(&points, line) {
+                // Joins lying on the line are found on the segments on both sides
+                let is_known = intersections.iter().any(|(_, known)| {
+                    known.position.distance_to(intersection.position) < POSITION_TOLERANCE
+                });
+                if !is_known {
+                    intersections.push((segment, intersection));
+                }
+            }
+        }
+        intersections
+    }
+
+    /// Outline of the stroke of the spline as closed splines (one for an open spline, one on each side of a loop)
+    pub fn stroke_outline(&self, style: &StrokeStyle, tolerance: f32) -> Vec<Spline> {
+        let cubics = self
+            .segments()
+            .map(|segment| segment.map(|p| p.position))
+            .collect::<Vec<_>>();
+        bezier_stroke_outline(&cubics, self.is_closed_loop, style, tolerance)
+            .iter()
+            .filter_map(|contour| Spline::from_cubics(contour, true))
+            .collect()
+    }
+
+    /// Unit tangent on one segment of the spline
+    pub fn segment_tangent(&self, segment: usize, t: f32) -> Option<Vector2> {
+        self.segment(segment)
+            .map(|points| bezier_tangent(&points, t))
+    }
+
+    /// Unit normal on one segment of the spline
+    pub fn segment_normal(&self, segment: usize, t: f32) -> Option<Vector2> {
+        self.segment(segment)
+            .map(|points| bezier_normal(&points, t))
+    }
+
+    /// Signed curvature on one segment of the spline
+    pub fn segment_curvature(&self, segment: usize, t: f32) -> Option<f32> {
+        self.segment(segment)
+            .map(|points| bezier_curvature(&points, t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{LineKind, StrokeCap, StrokeJoin};
+    use super::*;
+
+    fn make_spline() -> Spline {
+        let mut spline = Spline::new([
+            Vector2::new(300.0, 600.0),
+            Vector2::new(600.0, 300.0),
+            Vector2::new(900.0, 300.0),
+            Vector2::new(1200.0, 600.0),
+        ]);
+        spline.push_join(Vector2::new(1200.0, 1000.0));
+        spline.push_join(Vector2::new(500.0, 1000.0));
+        spline
+    }
+
+    fn positions(spline: &Spline, samples: usize) -> Vec<Vector2> {
+        spline
+            .segments()
+            .flat_map(|points| {
+                (0..=samples).map(move |i| evalute_bezier_curve(&points, i as f32 / samples as f32))
+            })
+            .collect()
+    }
+
+    fn distance_to_spline(spline: &Spline, position: Vector2) -> f32 {
+        positions(spline, 2000)
+            .iter()
+            .map(|p| p.distance_to(position))
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn segments_follow_joins() {
+        let mut spline = make_spline();
+        assert_eq!(spline.segment_count(), 3);
+        assert_eq!(spline.point_ids().count(), 4 * 3 - 2);
+        spline.close_loop();
+        assert_eq!(spline.segment_count(), 4);
+        assert_eq!(spline.point_ids().count(), 4 * 3);
+        let closing = spline.segment(3).unwrap();
+        assert_eq!(closing[0].position, spline.joins()[3].join.position);
+        assert_eq!(closing[3].position, spline.joins()[0].join.position);
+        assert!(spline.pop_segment());
+        assert_eq!(spline.segment_count(), 3);
+    }
+
+    #[test]
+    fn insert_join_keeps_shape() {
+        for closed in [false, true] {
+            let mut spline = make_spline();
+            if closed {
+                spline.close_loop();
+            }
+            let original = spline.clone();
+            let last_segment = spline.segment_count() - 1;
+            assert_eq!(spline.insert_join(1, 0.3), Some(2));
+            assert_eq!(
+                spline.insert_join(last_segment + 1, 0.6),
+                Some(last_segment + 2)
+            );
+            assert_eq!(spline.segment_count(), original.segment_count() + 2);
+            for position in positions(&spline, 20) {
+                assert!(distance_to_spline(&original, position) < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_inserted_join_restores_shape() {
+        for (closed, segment) in [(false, 1), (true, 3)] {
+            let mut spline = make_spline();
+            if closed {
+                spline.close_loop();
+            }
+            let original = spline.clone();
+            let join = spline.insert_join(segment, 0.5).unwrap();
+            assert!(spline.remove_join(join % spline.joins().len()));
+            assert_eq!(spline.segment_count(), original.segment_count());
+            for position in positions(&spline, 20) {
+                assert!(distance_to_spline(&original, position) < 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn remove_join_on_closed_seam() {
+        let mut spline = make_spline();
+        spline.close_loop();
+        assert!(spline.remove_join(0));
+        assert_eq!(spline.joins().len(), 3);
+        assert_eq!(spline.segment_count(), 3);
+        assert!(spline.remove_join(0));
+        assert_eq!(spline.segment_count(), 2);
+        assert!(!spline.remove_join(0));
+    }
+
+    #[test]
+    fn handle_constraints_follow_join_mode() {
+        let mut spline = make_spline();
+        let join = spline.joins()[1].join.position;
+        let previous_handle = SplinePointId::new(1, SplinePointKind::PreviousHandle);
+
+        spline.move_point(previous_handle, join + Vector2::new(-10.0, 0.0), true);
+        assert_eq!(
+            spline.joins()[1].next_handle.position,
+            join + Vector2::new(10.0, 0.0)
+        );
+
+        spline.set_join_mode(1, JoinMode::Smooth);
+        spline.move_point(previous_handle, join + Vector2::new(0.0, -50.0), true);
+        let next_handle = spline.joins()[1].next_handle.position;
+        assert!(next_handle.distance_to(join + Vector2::new(0.0, 10.0)) < 1e-3);
+
+        spline.set_join_mode(1, JoinMode::Corner);
+        spline.move_point(previous_handle, join + Vector2::new(30.0, 30.0), true);
+        assert_eq!(spline.joins()[1].next_handle.position, next_handle);
+
+        let join_id = SplinePointId::new(1, SplinePointKind::Join);
+        spline.move_point(join_id, join + Vector2::new(5.0, 5.0), true);
+        assert_eq!(
+            spline.joins()[1].next_handle.position,
+            next_handle + Vector2::new(5.0, 5.0)
+        );
+    }
+
+    #[test]
+    fn group_transform_keeps_constraints() {
+        let original = make_spline();
+        let offset = Vector2::new(20.0, -10.0);
+        let translate = |p: Vector2| p + offset;
+
+        // A transformed join carries its handles
+        let mut spline = original.clone();
+        let join = SplinePointId::new(1, SplinePointKind::Join);
+        spline.transform_points(&original, &[join], translate, true);
+        assert_eq!(
+            spline.joins()[1].next_handle.position,
+            original.joins()[1].next_handle.position + offset
+        );
+        let mut spline = original.clone();
+        spline.transform_points(&original, &[join], translate, false);
+        assert_eq!(
+            spline.joins()[1].next_handle.position,
+            original.joins()[1].next_handle.position
+        );
+
+        // A transformed handle drags the opposite symmetric handle
+        let mut spline = original.clone();
+        let previous_handle = SplinePointId::new(1, SplinePointKind::PreviousHandle);
+        spline.transform_points(&original, &[previous_handle], translate, true);
+        let joins = spline.joins();
+        assert_eq!(
+            joins[1].next_handle.position,
+            joins[1].join.position * 2.0 - joins[1].previous_handle.position
+        );
+    }
+
+    #[test]
+    fn nearest_point_on_spline() {
+        let spline = make_spline();
+        for (segment, t) in [(0, 0.3), (1, 0.5), (2, 0.9)] {
+            let points = spline.segment(segment).unwrap();
+            let position = evalute_bezier_curve(&points, t) + bezier_normal(&points, t) * 5.0;
+            let (nearest_segment, nearest) = spline.nearest_point(position).unwrap();
+            assert_eq!(nearest_segment, segment);
+            assert!((nearest.t - t).abs() < 1e-3);
+            assert!((nearest.distance - 5.0).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn intersections_between_splines() {
+        let spline = make_spline();
+        // Horizontal line crossing the first segment once and the loop back twice
+        let line = Spline::new([
+            Vector2::new(0.0, 500.0),
+            Vector2::new(500.0, 500.0),
+            Vector2::new(1000.0, 500.0),
+            Vector2::new(1500.0, 500.0),
+        ]);
+        let intersections = spline.intersections(&line);
+        assert_eq!(intersections.len(), 2);
+        for (segment, other_segment, intersection) in intersections {
+            assert_eq!(other_segment, 0);
+            assert!((intersection.position.y - 500.0).abs() < 1e-2);
+            let points = spline.segment(segment).unwrap();
+            assert!(
+                evalute_bezier_curve(&points, intersection.t1).distance_to(intersection.position)
+                    < 1e-3
+            );
+        }
+
+        let infinite_line = Line::new(
+            Vector2::new(0.0, 500.0),
+            Vector2::new(1.0, 500.0),
+            LineKind::Line,
+        );
+        let line_intersections = spline.line_intersections(infinite_line);
+        assert_eq!(line_intersections.len(), 2);
+        for ((segment, intersection), (other_segment, other_intersection)) in line_intersections
+            .iter()
+            .zip(spline.intersections(&line).iter().map(|(s, _, i)| (s, i)))
+        {
+            assert_eq!(segment, other_segment);
+            assert!(
+                intersection
+                    .position
+                    .distance_to(other_intersection.position)
+                    < 1e-2
+            );
+        }
+    }
+
+    #[test]
+    fn self_intersections_of_spline() {
+        let spline = make_spline();
+        assert!(spline.self_intersections().is_empty());
+
+        // Figure eight: the segment going back crosses the first one
+        let mut figure_eight = Spline::new([
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, -100.0),
+            Vector2::new(200.0, -100.0),
+            Vector2::new(300.0, 0.0),
+        ]);
+        figure_eight.push_join(Vector2::new(300.0, 200.0));
+        figure_eight.push_join(Vector2::new(0.0, -200.0));
+        let intersections = figure_eight.self_intersections();
+        assert!(!intersections.is_empty());
+        for (segment, other_segment, intersection) in intersections {
+            assert!(segment < other_segment);
+            let on_segment =
+                evalute_bezier_curve(&figure_eight.segment(segment).unwrap(), intersection.t1);
+            let on_other = evalute_bezier_curve(
+                &figure_eight.segment(other_segment).unwrap(),
+                intersection.t2,
+            );
+            assert!(on_segment.distance_to(on_other) < 1e-2);
+        }
+
+        // A closed loop is not crossing itself on the join closing it
+        figure_eight.pop_segment();
+        figure_eight.close_loop();
+        for (segment, other_segment, intersection) in figure_eight.self_intersections() {
+            assert!(
+                intersection
+                    .position
+                    .distance_to(figure_eight.joins()[0].join.position)
+                    > 1.0
+            );
+            assert!(segment <= other_segment);
+        }
+    }
+
+    #[test]
+    fn from_cubics_guesses_join_modes() {
+        let a = Vector2::new(0.0, 0.0);
+        let b = Vector2::new(100.0, 0.0);
+        let c = Vector2::new(200.0, 100.0);
+        let cubics = [
+            [a, Vector2::new(30.0, -30.0), Vector2::new(70.0, -30.0), b],
+            // Collinear handles of different lengths around b
+            [b, Vector2::new(160.0, 60.0), Vector2::new(200.0, 50.0), c],
+            // Mirrored handles around c and free handles around a
+            [c, Vector2::new(200.0, 150.0), Vector2::new(20.0, 60.0), a],
+        ];
+        let open = Spline::from_cubics(&cubics[..2], false).unwrap();
+        assert_eq!(open.joins().len(), 3);
+        assert_eq!(open.segment_count(), 2);
+        assert_eq!(open.joins()[1].mode, JoinMode::Smooth);
+        for (segment, cubic) in open.segments().zip(cubics.iter()) {
+            assert_eq!(segment.map(|p| p.position), *cubic);
+        }
+
+        let closed = Spline::from_cubics(&cubics, true).unwrap();
+        assert!(closed.is_closed_loop());
+        assert_eq!(closed.segment_count(), 3);
+        assert_eq!(closed.joins()[2].mode, JoinMode::Symmetric);
+        assert_eq!(closed.joins()[0].mode, JoinMode::Corner);
+        for (segment, cubic) in closed.segments().zip(cubics.iter()) {
+            assert_eq!(segment.map(|p| p.position), *cubic);
+        }
+
+        let single_loop = Spline::from_cubics(
+            &[[a, Vector2::new(200.0, 0.0), Vector2::new(0.0, 200.0), a]],
+            true,
+        )
+        .unwrap();
+        assert_eq!(single_loop.segment_count(), 2);
+        assert!(Spline::from_cubics(&[], false).is_none());
+    }
+
+    #[test]
+    fn stroke_outline_of_spline() {
+        let style = StrokeStyle {
+            width: 40.0,
+            join: StrokeJoin::Round,
+            cap: StrokeCap::Round,
+        };
+        let mut spline = make_spline();
+        let outlines = spline.stroke_outline(&style, 0.1);
+        assert_eq!(outlines.len(), 1);
+        assert!(outlines[0].is_closed_loop());
+        for position in positions(&outlines[0], 4) {
+            let distance = distance_to_spline(&spline, position);
+            assert!((distance - 20.0).abs() < 0.5, "{}", distance);
+        }
+
+        spline.close_loop();
+        let outlines = spline.stroke_outline(&style, 0.1);
+        assert_eq!(outlines.len(), 2);
+        for outline in outlines.iter() {
+            assert!(outline.is_closed_loop());
+            for position in positions(outline, 4) {
+                assert!(distance_to_spline(&spline, position) > 19.5);
+            }
+        }
+    }
+}
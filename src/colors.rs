@@ -7,3 +7,5 @@ pub static COLOR_BLUE: Color = Color::new(67, 130, 232, 255);
 pub static COLOR_DARK: Color = Color::new(89, 89, 89, 255);
 pub static COLOR_BLACK: Color = Color::new(0, 0, 0, 255);
 pub static COLOR_LIGHT: Color = Color::new(247, 251, 252, 255);
+pub static COLOR_GRID_MINOR: Color = Color::new(104, 104, 104, 255);
+pub static COLOR_GRID_MAJOR: Color = Color::new(130, 130, 130, 255);
@@ -4,6 +4,7 @@ use std::ffi::CStr;
 
 mod bezier;
 mod scenes;
+mod svg;
 
 mod colors;
 use colors::*;
@@ -33,12 +34,18 @@ fn main() {
     let mut scenes: Vec<Box<dyn scenes::Scene>> = vec![
         Box::<scenes::BezierCurveScene>::default(),
         Box::<scenes::BezierSplineScene>::default(),
+        Box::<scenes::BezierIntersectionScene>::default(),
+        Box::<scenes::BSplineScene>::default(),
+        Box::<scenes::CatmullRomScene>::default(),
     ];
     let mut current_scene: Option<usize> = None;
 
     const TITLE_FONT_SIZE: i32 = 80;
+    const STATUS_MESSAGE_DURATION: f32 = 3.0;
     let mut clock_divider = 0;
     let mut current_draw_time_text = String::new();
+    // Result of the last save/load and the time it was displayed
+    let mut status_message: Option<(String, instant::Instant)> = None;
     // let mut scene_to_load = None;
     while !rl_handle.window_should_close() {
         if let Some(scene_id) = current_scene {
@@ -48,6 +55,50 @@ fn main() {
         if rl_handle.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
             current_scene = None;
         }
+        let control_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shift_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        if let Some(scene_id) = current_scene {
+            if control_down && rl_handle.is_key_pressed(KeyboardKey::KEY_Z) {
+                let message = if shift_down {
+                    if scenes[scene_id].redo() {
+                        "Redo"
+                    } else {
+                        "Nothing to redo"
+                    }
+                } else if scenes[scene_id].undo() {
+                    "Undo"
+                } else {
+                    "Nothing to undo"
+                };
+                status_message = Some((message.to_string(), instant::Instant::now()));
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(scene_id) = current_scene {
+            if control_down && rl_handle.is_key_pressed(KeyboardKey::KEY_S) {
+                status_message = Some((
+                    save_scene(scenes[scene_id].as_ref()),
+                    instant::Instant::now(),
+                ));
+            } else if control_down && rl_handle.is_key_pressed(KeyboardKey::KEY_O) {
+                status_message = Some((
+                    load_scene(scenes[scene_id].as_mut()),
+                    instant::Instant::now(),
+                ));
+            } else if control_down && rl_handle.is_key_pressed(KeyboardKey::KEY_E) {
+                status_message = Some((
+                    export_scene_svg(scenes[scene_id].as_ref()),
+                    instant::Instant::now(),
+                ));
+            } else if control_down && rl_handle.is_key_pressed(KeyboardKey::KEY_I) {
+                status_message = Some((
+                    import_scene_svg(scenes[scene_id].as_mut()),
+                    instant::Instant::now(),
+                ));
+            }
+        }
         let help_page_requested = rl_handle.is_key_down(KeyboardKey::KEY_H);
 
         // Draw frame
@@ -78,6 +129,18 @@ fn main() {
                     COLOR_LIGHT,
                 );
 
+                if let Some((message, time)) = &status_message {
+                    if time.elapsed().as_secs_f32() < STATUS_MESSAGE_DURATION {
+                        rl_draw_handle.draw_text(
+                            message.as_str(),
+                            40,
+                            screen_height - 40,
+                            18,
+                            COLOR_LIGHT,
+                        );
+                    }
+                }
+
                 if help_page_requested {
                     draw_help_page(&mut rl_draw_handle, scenes[scene_id].help_text());
                 }
@@ -126,6 +189,68 @@ fn main() {
     }
 }
 
+/// Write the scene to its save file and return a message describing the result
+#[cfg(not(target_arch = "wasm32"))]
+fn save_scene(scene: &dyn scenes::Scene) -> String {
+    let Some(file_name) = scene.save_file_name() else {
+        return format!("{} can't be saved", scene.get_title());
+    };
+    match scene
+        .to_json()
+        .and_then(|json| std::fs::write(file_name, json).map_err(|e| e.to_string()))
+    {
+        Ok(()) => format!("Saved to {}", file_name),
+        Err(e) => format!("Failed to save {}: {}", file_name, e),
+    }
+}
+
+/// Read the scene from its save file and return a message describing the result
+#[cfg(not(target_arch = "wasm32"))]
+fn load_scene(scene: &mut dyn scenes::Scene) -> String {
+    let Some(file_name) = scene.save_file_name().map(str::to_string) else {
+        return format!("{} can't be loaded", scene.get_title());
+    };
+    match std::fs::read_to_string(&file_name)
+        .map_err(|e| e.to_string())
+        .and_then(|json| scene.load_json(&json))
+    {
+        Ok(()) => format!("Loaded {}", file_name),
+        Err(e) => format!("Failed to load {}: {}", file_name, e),
+    }
+}
+
+/// Export the scene next to its save file as SVG and return a message describing the result
+#[cfg(not(target_arch = "wasm32"))]
+fn export_scene_svg(scene: &dyn scenes::Scene) -> String {
+    let Some(file_name) = scene.save_file_name() else {
+        return format!("{} can't be exported", scene.get_title());
+    };
+    let file_name = std::path::Path::new(file_name).with_extension("svg");
+    match scene
+        .to_svg()
+        .and_then(|svg| std::fs::write(&file_name, svg).map_err(|e| e.to_string()))
+    {
+        Ok(()) => format!("Exported to {}", file_name.display()),
+        Err(e) => format!("Failed to export {}: {}", file_name.display(), e),
+    }
+}
+
+/// Replace the scene curves with the SVG file next to its save file and return a message describing the result
+#[cfg(not(target_arch = "wasm32"))]
+fn import_scene_svg(scene: &mut dyn scenes::Scene) -> String {
+    let Some(file_name) = scene.save_file_name() else {
+        return format!("{} can't import SVG", scene.get_title());
+    };
+    let file_name = std::path::Path::new(file_name).with_extension("svg");
+    match std::fs::read_to_string(&file_name)
+        .map_err(|e| e.to_string())
+        .and_then(|svg| scene.import_svg(&svg))
+    {
+        Ok(()) => format!("Imported {}", file_name.display()),
+        Err(e) => format!("Failed to import {}: {}", file_name.display(), e),
+    }
+}
+
 fn draw_background(rl_draw_handle: &mut RaylibDrawHandle, tile_texture: &Texture2D) {
     let screen_width = rl_draw_handle.get_screen_width();
     let screen_height = rl_draw_handle.get_screen_height();
@@ -1,10 +1,21 @@
-use raylib::RaylibHandle;
 use raylib::drawing::RaylibDrawHandle;
+use raylib::RaylibHandle;
 
 mod curve;
 pub use curve::BezierCurveScene;
 mod spline;
 pub use spline::BezierSplineScene;
+mod intersection;
+pub use intersection::BezierIntersectionScene;
+mod bspline;
+pub use bspline::BSplineScene;
+mod catmull_rom;
+pub use catmull_rom::CatmullRomScene;
+mod camera;
+mod grid;
+mod history;
+mod save;
+mod selection;
 
 pub trait Scene {
     fn get_title(&self) -> &str;
@@ -14,4 +25,32 @@ pub trait Scene {
     fn update(&mut self, rl_handle: &mut RaylibHandle);
     /// Draw one frame of the scene
     fn draw(&mut self, rl_handle: &mut RaylibDrawHandle);
+    /// Name of the file used to save the scene (None if the scene can't be saved)
+    fn save_file_name(&self) -> Option<&str> {
+        None
+    }
+    /// Serialize the scene state to JSON
+    fn to_json(&self) -> Result<String, String> {
+        Err(format!("{} can't be saved", self.get_title()))
+    }
+    /// Restore the scene state from JSON (the scene is left untouched on error)
+    fn load_json(&mut self, _json: &str) -> Result<(), String> {
+        Err(format!("{} can't be loaded", self.get_title()))
+    }
+    /// Export the curves of the scene as a standalone SVG document
+    fn to_svg(&self) -> Result<String, String> {
+        Err(format!("{} can't be exported", self.get_title()))
+    }
+    /// Replace the curves of the scene with the ones of an SVG document or raw SVG path data
+    fn import_svg(&mut self, _svg: &str) -> Result<(), String> {
+        Err(format!("{} can't import SVG", self.get_title()))
+    }
+    /// Revert the last edit, returns false when there is nothing to undo
+    fn undo(&mut self) -> bool {
+        false
+    }
+    /// Apply again the last undone edit, returns false when there is nothing to redo
+    fn redo(&mut self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,409 @@
+use crate::bezier::*;
+use crate::colors::*;
+use crate::scenes::camera::SceneCamera;
+use crate::scenes::grid::Grid;
+use crate::scenes::selection::{draw_curve_hover, CURVE_HOVER_DISTANCE};
+use crate::scenes::Scene;
+use raylib::prelude::*;
+use std::ffi::CStr;
+
+/// Distance in pixels from a knot marker within which it can be dragged
+const KNOT_GRAB_DISTANCE: f32 = 8.0;
+/// Weight factor applied by each step of the mouse wheel
+const WEIGHT_STEP: f32 = 1.1;
+const MIN_WEIGHT: f32 = 0.1;
+const MAX_WEIGHT: f32 = 10.0;
+
+pub struct BSplineScene {
+    spline: BSpline,
+    is_clamped: bool,
+    dragged_point: Option<usize>,
+    /// First and last index of the knots being dragged (equal knots are grabbed together until they are pulled apart)
+    dragged_knot: Option<(usize, usize)>,
+    /// Parameter and position of the point of the curve closest to the mouse (when close enough)
+    hovered_curve_point: Option<(f32, Vector2)>,
+    camera: SceneCamera,
+    grid: Grid,
+}
+impl BSplineScene {
+    /// Screen area of the knot vector editor
+    fn knot_editor_area(screen_width: i32, screen_height: i32) -> Rectangle {
+        Rectangle::new(
+            40.0,
+            screen_height as f32 - 110.0,
+            screen_width as f32 - 80.0,
+            30.0,
+        )
+    }
+
+    /// Indices of the knots equal to the one under the mouse
+    fn knot_under(&self, area: Rectangle, mouse_position: Vector2) -> Option<(usize, usize)> {
+        if mouse_position.y < area.y || mouse_position.y > area.y + area.height {
+            return None;
+        }
+        let knots = self.spline.knots();
+        let index = knots
+            .iter()
+            .enumerate()
+            .map(|(i, knot)| (i, (area.x + knot * area.width - mouse_position.x).abs()))
+            .filter(|(_, distance)| *distance <= KNOT_GRAB_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))?
+            .0;
+        let first = knots.iter().position(|knot| *knot == knots[index])?;
+        let last = knots.iter().rposition(|knot| *knot == knots[index])?;
+        Some((first, last))
+    }
+}
+impl Scene for BSplineScene {
+    fn get_title(&self) -> &str {
+        "B-Spline Scene"
+    }
+
+    fn has_background(&self) -> bool {
+        self.grid.is_visible
+    }
+
+    fn help_text(&self) -> Vec<&str> {
+        [
+            "ESC - Go back to main menu",
+            "MOUSE CLICK - Move point",
+            "SPACE - Add new control point at mouse position (the knots become uniform)",
+            "BACKSPACE - Remove last control point (the knots become uniform)",
+            "UP / DOWN - Increase / Decrease the degree",
+            "MOUSE WHEEL OVER A POINT - Change its weight (NURBS)",
+            "K - Insert a knot at the hovered point of the curve (the curve does not change)",
+            "DRAG KNOTS AT THE BOTTOM - Edit the knot vector",
+            "MOUSE WHEEL / MIDDLE DRAG - Zoom / Pan the view",
+            "ALT WHILE DRAGGING - Toggle snapping to the grid",
+        ]
+        .to_vec()
+    }
+
+    fn update(&mut self, rl_handle: &mut RaylibHandle) {
+        // Update inputs
+        // The mouse wheel changes the weight of the point under the mouse instead of zooming
+        let wheel = rl_handle.get_mouse_wheel_move();
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        match self
+            .spline
+            .points()
+            .iter()
+            .position(|p| p.is_under(mouse_position, zoom))
+        {
+            Some(index) if wheel != 0.0 => {
+                let point = &mut self.spline.points_mut()[index];
+                point.weight =
+                    (point.weight * WEIGHT_STEP.powf(wheel)).clamp(MIN_WEIGHT, MAX_WEIGHT);
+            }
+            _ => self.camera.update(rl_handle),
+        }
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let screen_mouse_position = rl_handle.get_mouse_position();
+        let drag_position = self.grid.drag_position(
+            mouse_position,
+            rl_handle.is_key_down(KeyboardKey::KEY_LEFT_ALT)
+                || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_ALT),
+        );
+        let knot_area =
+            Self::knot_editor_area(rl_handle.get_screen_width(), rl_handle.get_screen_height());
+
+        // Scene computation
+        for point in self.spline.points_mut() {
+            point.udpate_gui(mouse_position, zoom);
+        }
+        self.hovered_curve_point = None;
+        if self.dragged_point.is_none()
+            && !self
+                .spline
+                .points()
+                .iter()
+                .any(|p| p.is_under(mouse_position, zoom))
+        {
+            let nearest = self
+                .spline
+                .knot_spans()
+                .into_iter()
+                .zip(self.spline.to_rational_beziers())
+                .map(|((u0, u1), bezier)| {
+                    let nearest = nearest_point_on_bezier(&bezier, mouse_position);
+                    (u0 + (u1 - u0) * nearest.t, nearest)
+                })
+                .min_by(|a, b| a.1.distance.total_cmp(&b.1.distance));
+            if let Some((u, nearest)) = nearest {
+                if nearest.distance * zoom < CURVE_HOVER_DISTANCE {
+                    self.hovered_curve_point = Some((u, nearest.position));
+                }
+            }
+        }
+        if let Some(index) = self.dragged_point {
+            self.spline.points_mut()[index].position = drag_position;
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.spline.points_mut()[index].set_selected(false);
+                self.dragged_point = None;
+            }
+        } else if let Some((first, last)) = self.dragged_knot {
+            let value = ((screen_mouse_position.x - knot_area.x) / knot_area.width).clamp(0.0, 1.0);
+            let stack_value = self.spline.knots()[first];
+            // Pull the knot on the side of the mouse out of the stack
+            let index = if value > stack_value { last } else { first };
+            if value != stack_value {
+                self.spline.set_knot(index, value);
+                self.dragged_knot = Some((index, index));
+            }
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.dragged_knot = None;
+            }
+        } else if rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            if let Some(knots) = self.knot_under(knot_area, screen_mouse_position) {
+                self.dragged_knot = Some(knots);
+            } else if let Some(index) = self.spline.points().iter().position(|p| p.is_hovered()) {
+                self.spline.points_mut()[index].set_selected(true);
+                self.dragged_point = Some(index);
+            }
+        }
+
+        if let Some(key) = rl_handle.get_key_pressed() {
+            if self.dragged_point.is_none() && self.dragged_knot.is_none() {
+                match key {
+                    KeyboardKey::KEY_SPACE => {
+                        self.spline.push_point(mouse_position, self.is_clamped);
+                    }
+                    KeyboardKey::KEY_BACKSPACE => {
+                        self.spline.pop_point(self.is_clamped);
+                    }
+                    KeyboardKey::KEY_UP => {
+                        self.spline
+                            .set_degree(self.spline.degree() + 1, self.is_clamped);
+                    }
+                    KeyboardKey::KEY_DOWN => {
+                        self.spline
+                            .set_degree(self.spline.degree() - 1, self.is_clamped);
+                    }
+                    KeyboardKey::KEY_K => {
+                        if let Some((u, _)) = self.hovered_curve_point {
+                            self.spline.insert_knot(u);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, rl_draw_handle: &mut RaylibDrawHandle) {
+        let clamped_toggle_text = CStr::from_bytes_with_nul(b"Clamped knots\0").unwrap();
+        let uniform_button_text = CStr::from_bytes_with_nul(b"Uniform knots\0").unwrap();
+        let circle_button_text = CStr::from_bytes_with_nul(b"Circle preset\0").unwrap();
+
+        // Tangent and curvature of the spline at the hovered point
+        let hovered_frame = self.hovered_curve_point.and_then(|(u, position)| {
+            let (first, second) = self.spline.derivatives(u);
+            let speed = first.length();
+            (speed > 1e-6).then(|| {
+                (
+                    position,
+                    first * (1.0 / speed),
+                    (first.x * second.y - first.y * second.x) / (speed * speed * speed),
+                )
+            })
+        });
+
+        // Draw the spline in world space
+        {
+            let visible_area = self.camera.visible_area(
+                rl_draw_handle.get_screen_width(),
+                rl_draw_handle.get_screen_height(),
+            );
+            let zoom = self.camera.camera().zoom;
+            let mut rl_mode_2d = rl_draw_handle.begin_mode2D(self.camera.camera());
+            self.grid.draw(&mut rl_mode_2d, visible_area, zoom);
+            for line_points in self.spline.points().windows(2) {
+                rl_mode_2d.draw_line_ex(
+                    line_points[0].position,
+                    line_points[1].position,
+                    3.0 / zoom,
+                    COLOR_RED,
+                );
+            }
+            for bezier in self.spline.to_rational_beziers() {
+                draw_bezier_line(
+                    &bezier,
+                    &mut rl_mode_2d,
+                    5.0 / zoom,
+                    COLOR_GREEN,
+                    DRAW_TOLERANCE / zoom,
+                );
+            }
+            let knot_size = Vector2::new(POINTS_RADIUS, POINTS_RADIUS) * (1.0 / zoom);
+            for knot_point in self.spline.knot_points() {
+                rl_mode_2d.draw_rectangle_v(knot_point - knot_size * 0.5, knot_size, COLOR_YELLOW);
+            }
+            for point in self.spline.points() {
+                point.draw(&mut rl_mode_2d, zoom);
+            }
+            if let Some((position, tangent, _)) = hovered_frame {
+                draw_curve_frame(
+                    &mut rl_mode_2d,
+                    position,
+                    tangent,
+                    Vector2::new(-tangent.y, tangent.x),
+                    zoom,
+                );
+            }
+        }
+
+        for point in self
+            .spline
+            .points()
+            .iter()
+            .filter(|p| p.weight != 1.0 || p.is_hovered)
+        {
+            let screen_position = self.camera.world_to_screen(point.position);
+            rl_draw_handle.draw_text(
+                format!("w = {:.2}", point.weight).as_str(),
+                screen_position.x as i32 + 20,
+                screen_position.y as i32 - 30,
+                18,
+                COLOR_LIGHT,
+            );
+        }
+        if let Some((position, _, curvature)) = hovered_frame {
+            draw_curvature_label(
+                rl_draw_handle,
+                self.camera.world_to_screen(position),
+                curvature,
+            );
+        }
+        if let Some((u, position)) = self.hovered_curve_point {
+            draw_curve_hover(
+                rl_draw_handle,
+                self.camera.world_to_screen(position),
+                &format!("u = {:.3}", u),
+            );
+        }
+
+        // Draw GUI Controls (screen space)
+        self.grid.draw_gui(rl_draw_handle);
+        let was_clamped = self.is_clamped;
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(40.0, 20.0, 300.0, 25.0),
+            Some(clamped_toggle_text),
+            &mut self.is_clamped,
+        );
+        if self.is_clamped != was_clamped {
+            self.spline.reset_knots(self.is_clamped);
+        }
+        if rl_draw_handle.gui_button(
+            Rectangle::new(40.0, 50.0, 300.0, 25.0),
+            Some(uniform_button_text),
+        ) {
+            self.spline.reset_knots(self.is_clamped);
+        }
+        rl_draw_handle.draw_text(
+            format!(
+                "Degree: {} ({} points)",
+                self.spline.degree(),
+                self.spline.points().len()
+            )
+            .as_str(),
+            40,
+            85,
+            18,
+            COLOR_LIGHT,
+        );
+        if rl_draw_handle.gui_button(
+            Rectangle::new(40.0, 115.0, 300.0, 25.0),
+            Some(circle_button_text),
+        ) {
+            // Exact circle (quadratic NURBS curve)
+            self.spline = BSpline::circle(Vector2::new(750.0, 500.0), 300.0);
+            self.is_clamped = true;
+        }
+
+        // Knot vector editor
+        let area = Self::knot_editor_area(
+            rl_draw_handle.get_screen_width(),
+            rl_draw_handle.get_screen_height(),
+        );
+        let knots_text = self
+            .spline
+            .knots()
+            .iter()
+            .map(|knot| format!("{:.2}", knot))
+            .collect::<Vec<_>>()
+            .join(" ");
+        rl_draw_handle.draw_text(
+            format!("Knots: {}", knots_text).as_str(),
+            area.x as i32,
+            area.y as i32 - 25,
+            18,
+            COLOR_LIGHT,
+        );
+        let (start, end) = self.spline.domain();
+        rl_draw_handle.draw_rectangle_rec(
+            Rectangle::new(
+                area.x + start * area.width,
+                area.y,
+                (end - start) * area.width,
+                area.height,
+            ),
+            COLOR_GREEN.fade(0.3),
+        );
+        rl_draw_handle.draw_rectangle_lines_ex(area, 2.0, COLOR_LIGHT);
+        let knots = self.spline.knots();
+        let mut i = 0;
+        while i < knots.len() {
+            let multiplicity = knots[i..].iter().take_while(|k| **k == knots[i]).count();
+            let is_dragged = self
+                .dragged_knot
+                .is_some_and(|(first, last)| first <= i && i <= last);
+            let x = area.x + knots[i] * area.width;
+            rl_draw_handle.draw_rectangle_v(
+                Vector2::new(x - 4.0, area.y),
+                Vector2::new(8.0, area.height),
+                if is_dragged {
+                    COLOR_LIGHT
+                } else {
+                    COLOR_YELLOW
+                },
+            );
+            if multiplicity > 1 {
+                rl_draw_handle.draw_text(
+                    format!("x{}", multiplicity).as_str(),
+                    x as i32 - 8,
+                    (area.y + area.height) as i32 + 5,
+                    18,
+                    COLOR_LIGHT,
+                );
+            }
+            i += multiplicity;
+        }
+    }
+}
+impl Default for BSplineScene {
+    fn default() -> Self {
+        Self {
+            spline: BSpline::new(
+                &[
+                    Vector2::new(300.0, 600.0),
+                    Vector2::new(500.0, 250.0),
+                    Vector2::new(750.0, 700.0),
+                    Vector2::new(1000.0, 250.0),
+                    Vector2::new(1250.0, 700.0),
+                    Vector2::new(1450.0, 350.0),
+                ],
+                3,
+                true,
+            )
+            .unwrap(),
+            is_clamped: true,
+            dragged_point: None,
+            dragged_knot: None,
+            hovered_curve_point: None,
+            camera: SceneCamera::default(),
+            grid: Grid::default(),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use raylib::prelude::*;
+
+const ZOOM_SPEED: f32 = 0.1;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.0;
+
+/// Pan & zoom camera of the editing scenes
+/// Scene content lives in world space while GUI controls stay in screen space.
+pub struct SceneCamera {
+    camera: Camera2D,
+}
+impl SceneCamera {
+    pub fn camera(&self) -> Camera2D {
+        self.camera
+    }
+
+    pub fn screen_to_world(&self, position: Vector2) -> Vector2 {
+        (position - self.camera.offset) / self.camera.zoom + self.camera.target
+    }
+
+    pub fn world_to_screen(&self, position: Vector2) -> Vector2 {
+        (position - self.camera.target) * self.camera.zoom + self.camera.offset
+    }
+
+    /// World area covered by a screen of the given size
+    pub fn visible_area(&self, screen_width: i32, screen_height: i32) -> Rectangle {
+        let top_left = self.screen_to_world(Vector2::zero());
+        Rectangle::new(
+            top_left.x,
+            top_left.y,
+            screen_width as f32 / self.camera.zoom,
+            screen_height as f32 / self.camera.zoom,
+        )
+    }
+
+    /// Mouse position in world space
+    pub fn mouse_position(&self, rl_handle: &RaylibHandle) -> Vector2 {
+        self.screen_to_world(Vector2::new(
+            rl_handle.get_mouse_x() as f32,
+            rl_handle.get_mouse_y() as f32,
+        ))
+    }
+
+    /// Scale the view while keeping the world point under the given screen position in place
+    pub fn zoom_at(&mut self, screen_position: Vector2, factor: f32) {
+        let world_position = self.screen_to_world(screen_position);
+        self.camera.offset = screen_position;
+        self.camera.target = world_position;
+        self.camera.zoom = (self.camera.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Move the view by a screen space offset
+    pub fn pan(&mut self, screen_delta: Vector2) {
+        self.camera.target -= screen_delta / self.camera.zoom;
+    }
+
+    /// Zoom around the mouse with the wheel and pan while the middle button is down
+    pub fn update(&mut self, rl_handle: &RaylibHandle) {
+        let wheel = rl_handle.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            let mouse_position = Vector2::new(
+                rl_handle.get_mouse_x() as f32,
+                rl_handle.get_mouse_y() as f32,
+            );
+            self.zoom_at(mouse_position, 1.0 + wheel * ZOOM_SPEED);
+        }
+        if rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE) {
+            self.pan(rl_handle.get_mouse_delta());
+        }
+    }
+}
+impl Default for SceneCamera {
+    fn default() -> Self {
+        Self {
+            camera: Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 1.0,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_point_under_cursor() {
+        let mut camera = SceneCamera::default();
+        camera.pan(Vector2::new(-120.0, 35.0));
+        let cursor = Vector2::new(300.0, 200.0);
+        let world_position = camera.screen_to_world(cursor);
+        camera.zoom_at(cursor, 1.5);
+        camera.zoom_at(cursor, 1.5);
+        assert!((camera.camera().zoom - 2.25).abs() < 1e-5);
+        assert!(camera.screen_to_world(cursor).distance_to(world_position) < 1e-3);
+        camera.zoom_at(cursor, 1000.0);
+        assert_eq!(camera.camera().zoom, MAX_ZOOM);
+        assert!(camera.world_to_screen(world_position).distance_to(cursor) < 1e-2);
+    }
+
+    #[test]
+    fn pan_follows_mouse() {
+        let mut camera = SceneCamera::default();
+        camera.zoom_at(Vector2::zero(), 2.0);
+        let world_position = camera.screen_to_world(Vector2::new(100.0, 100.0));
+        camera.pan(Vector2::new(30.0, -10.0));
+        assert!(
+            camera
+                .screen_to_world(Vector2::new(130.0, 90.0))
+                .distance_to(world_position)
+                < 1e-3
+        );
+    }
+}
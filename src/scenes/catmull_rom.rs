@@ -0,0 +1,164 @@
+use crate::bezier::*;
+use crate::colors::*;
+use crate::scenes::camera::SceneCamera;
+use crate::scenes::grid::Grid;
+use crate::scenes::Scene;
+use raylib::prelude::*;
+use std::ffi::CStr;
+
+const ALPHA_PRESET_TEXTS: [(f32, &[u8]); 3] = [
+    (0.0, b"Uniform\0"),
+    (0.5, b"Centripetal\0"),
+    (1.0, b"Chordal\0"),
+];
+
+pub struct CatmullRomScene {
+    spline: CatmullRomSpline,
+    dragged_point: Option<usize>,
+    camera: SceneCamera,
+    grid: Grid,
+}
+impl Scene for CatmullRomScene {
+    fn get_title(&self) -> &str {
+        "Catmull-Rom Scene"
+    }
+
+    fn has_background(&self) -> bool {
+        self.grid.is_visible
+    }
+
+    fn help_text(&self) -> Vec<&str> {
+        [
+            "ESC - Go back to main menu",
+            "MOUSE CLICK - Move point",
+            "SPACE - Add new point at mouse position (the spline goes through every point)",
+            "BACKSPACE - Remove last point",
+            "MOUSE WHEEL / MIDDLE DRAG - Zoom / Pan the view",
+            "ALT WHILE DRAGGING - Toggle snapping to the grid",
+        ]
+        .to_vec()
+    }
+
+    fn update(&mut self, rl_handle: &mut RaylibHandle) {
+        // Update inputs
+        self.camera.update(rl_handle);
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let drag_position = self.grid.drag_position(
+            mouse_position,
+            rl_handle.is_key_down(KeyboardKey::KEY_LEFT_ALT)
+                || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_ALT),
+        );
+
+        // Scene computation
+        for point in self.spline.points_mut() {
+            point.udpate_gui(mouse_position, zoom);
+        }
+        if let Some(index) = self.dragged_point {
+            self.spline.points_mut()[index].position = drag_position;
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.spline.points_mut()[index].set_selected(false);
+                self.dragged_point = None;
+            }
+        } else if rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            if let Some(index) = self.spline.points().iter().position(|p| p.is_hovered()) {
+                self.spline.points_mut()[index].set_selected(true);
+                self.dragged_point = Some(index);
+            }
+        }
+
+        if let Some(key) = rl_handle.get_key_pressed() {
+            if self.dragged_point.is_none() {
+                match key {
+                    KeyboardKey::KEY_SPACE => {
+                        self.spline.push_point(mouse_position);
+                    }
+                    KeyboardKey::KEY_BACKSPACE => {
+                        self.spline.pop_point();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, rl_draw_handle: &mut RaylibDrawHandle) {
+        let alpha_slider_text = CStr::from_bytes_with_nul(b"Alpha\0").unwrap();
+
+        // Draw the spline in world space
+        {
+            let visible_area = self.camera.visible_area(
+                rl_draw_handle.get_screen_width(),
+                rl_draw_handle.get_screen_height(),
+            );
+            let zoom = self.camera.camera().zoom;
+            let mut rl_mode_2d = rl_draw_handle.begin_mode2D(self.camera.camera());
+            self.grid.draw(&mut rl_mode_2d, visible_area, zoom);
+            // The handles of the Bezier segments are computed, so they are drawn smaller than the points
+            for cubic in self.spline.to_cubics() {
+                let handle = |position: Vector2| BasicPoint {
+                    radius: POINTS_RADIUS * 0.5,
+                    ..BasicPoint::new(position, COLOR_LIGHT)
+                };
+                draw_bezier(&cubic.map(handle), &mut rl_mode_2d, None, zoom);
+            }
+            for point in self.spline.points() {
+                point.draw(&mut rl_mode_2d, zoom);
+            }
+        }
+
+        // Draw GUI Controls (screen space)
+        self.grid.draw_gui(rl_draw_handle);
+        rl_draw_handle.gui_slider_bar(
+            Rectangle::new(100.0, 20.0, 240.0, 25.0),
+            Some(alpha_slider_text),
+            None,
+            &mut self.spline.alpha,
+            0.0,
+            1.0,
+        );
+        for (i, (alpha, text)) in ALPHA_PRESET_TEXTS.into_iter().enumerate() {
+            let mut is_active = self.spline.alpha == alpha;
+            rl_draw_handle.gui_toggle(
+                Rectangle::new(40.0 + 102.0 * i as f32, 50.0, 96.0, 25.0),
+                Some(CStr::from_bytes_with_nul(text).unwrap()),
+                &mut is_active,
+            );
+            if is_active {
+                self.spline.alpha = alpha;
+            }
+        }
+        rl_draw_handle.draw_text(
+            format!(
+                "alpha = {:.2} ({} points)",
+                self.spline.alpha,
+                self.spline.points().len()
+            )
+            .as_str(),
+            40,
+            85,
+            18,
+            COLOR_LIGHT,
+        );
+    }
+}
+impl Default for CatmullRomScene {
+    fn default() -> Self {
+        Self {
+            spline: CatmullRomSpline::new(
+                &[
+                    Vector2::new(300.0, 600.0),
+                    Vector2::new(500.0, 300.0),
+                    Vector2::new(750.0, 650.0),
+                    Vector2::new(800.0, 620.0),
+                    Vector2::new(1100.0, 300.0),
+                    Vector2::new(1350.0, 600.0),
+                ],
+                0.5,
+            ),
+            dragged_point: None,
+            camera: SceneCamera::default(),
+            grid: Grid::default(),
+        }
+    }
+}
@@ -1,18 +1,45 @@
 use crate::bezier::*;
 use crate::colors::*;
+use crate::scenes::camera::SceneCamera;
+use crate::scenes::grid::Grid;
+use crate::scenes::history::{History, HISTORY_CAPACITY};
+use crate::scenes::save::{array_to_vector, vector_to_array, CurveSceneSave};
+use crate::scenes::selection::*;
 use crate::scenes::Scene;
+use crate::svg::svg_document;
 use raylib::prelude::*;
+use std::f32::consts::PI;
 use std::ffi::CStr;
 
 const T_ANIMATION_SPEED: f32 = 0.005;
+/// Maximum distance between the curve and its cubic approximation in the SVG export
+const SVG_TOLERANCE: f32 = 0.5;
+/// Weight factor applied by each step of the mouse wheel
+const WEIGHT_STEP: f32 = 1.1;
+const MIN_WEIGHT: f32 = 0.1;
+const MAX_WEIGHT: f32 = 10.0;
 
 pub struct BezierCurveScene {
     points: Vec<BasicPoint>,
     animated: bool,
     animation_bounce: bool,
-    has_point_selected: bool,
     debug_draw: bool,
     t: f32,
+    /// Transform of the selected points in progress with the positions of every point when it started
+    transform: Option<(GroupTransform, Vec<Vector2>)>,
+    /// Corner of the rubber band rectangle being dragged
+    box_selection_start: Option<Vector2>,
+    /// Pivot of the rotations and scales (the center of the selection when not set)
+    pivot: Option<Vector2>,
+    /// Point of the curve closest to the mouse (when close enough)
+    hovered_curve_point: Option<NearestPoint>,
+    /// Arc length table of the curve with the points it was computed for (it is only computed again after an edit)
+    arc_length_table: Option<(Vec<WeightedPoint>, ArcLengthTable)>,
+    /// Point of the curve closest to a position with the points and the position it was computed for
+    nearest_curve_point: Option<(Vec<WeightedPoint>, Vector2, NearestPoint)>,
+    camera: SceneCamera,
+    grid: Grid,
+    history: History<CurveSceneSave>,
 }
 impl Scene for BezierCurveScene {
     fn get_title(&self) -> &str {
@@ -20,7 +47,7 @@ impl Scene for BezierCurveScene {
     }
 
     fn has_background(&self) -> bool {
-        false
+        self.grid.is_visible
     }
 
     fn help_text(&self) -> Vec<&str> {
@@ -28,53 +55,138 @@ impl Scene for BezierCurveScene {
             "ESC - Go back to main menu",
             "SPACE - Add new control point on mouse position",
             "BACKSPACE - Remove last point",
-            "MOUSE CLICK - Move point",
+            "MOUSE CLICK - Select and move points",
+            "SHIFT+CLICK - Add or remove a point from the selection",
+            "MOUSE DRAG ON EMPTY SPACE - Box select (SHIFT to add to the selection)",
+            "R / S - Rotate / Scale the selection with the mouse (click to apply, right click to cancel)",
+            "P - Set the pivot on mouse position (again to use the center of the selection)",
+            "MOUSE WHEEL / MIDDLE DRAG - Zoom / Pan the view",
+            "MOUSE WHEEL ON A POINT - Change the weight of the point (rational curve)",
+            "ALT WHILE DRAGGING - Toggle snapping to the grid",
+            "CTRL+Z / CTRL+SHIFT+Z - Undo / Redo",
+            "CTRL+S - Save the curve to bezier_curve.json",
+            "CTRL+O - Load the curve from bezier_curve.json",
+            "CTRL+E - Export the curve to bezier_curve.svg",
         ]
         .to_vec()
     }
 
     fn update(&mut self, rl_handle: &mut RaylibHandle) {
         // Update inputs
-        let mouse_position = Vector2::new(
-            rl_handle.get_mouse_x() as f32,
-            rl_handle.get_mouse_y() as f32,
+        // The mouse wheel changes the weight of the point under the mouse instead of zooming
+        let wheel = rl_handle.get_mouse_wheel_move();
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let mut is_wheel_edit = false;
+        match self
+            .points
+            .iter()
+            .position(|p| p.is_under(mouse_position, zoom))
+        {
+            Some(index) if wheel != 0.0 && self.transform.is_none() => {
+                let point = &mut self.points[index];
+                point.weight =
+                    (point.weight * WEIGHT_STEP.powf(wheel)).clamp(MIN_WEIGHT, MAX_WEIGHT);
+                // Scrolling the wheel is a single edit
+                is_wheel_edit = true;
+            }
+            _ => self.camera.update(rl_handle),
+        }
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let drag_position = self.grid.drag_position(
+            mouse_position,
+            rl_handle.is_key_down(KeyboardKey::KEY_LEFT_ALT)
+                || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_ALT),
         );
+        let shift_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        let control_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
 
         // Scene computation
         // Update points
         for point in self.points.iter_mut() {
-            point.udpate_gui(mouse_position);
-            if point.is_selected {
-                point.set_position(mouse_position, false);
+            point.udpate_gui(mouse_position, zoom);
+        }
+        self.hovered_curve_point = None;
+        if self.transform.is_none()
+            && self.box_selection_start.is_none()
+            && !self.points.iter().any(|p| p.is_under(mouse_position, zoom))
+        {
+            let nearest = self.nearest_curve_point(mouse_position);
+            if nearest.distance * zoom < CURVE_HOVER_DISTANCE {
+                self.hovered_curve_point = Some(nearest);
             }
         }
-        if rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
-            if !self.has_point_selected {
+        if let Some((transform, original_positions)) = &self.transform {
+            // Moves follow the (snapped) dragged point, rotations and scales follow the mouse
+            let (transform_position, is_done) = if transform.kind == TransformKind::Move {
+                (
+                    drag_position,
+                    !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
+                )
+            } else {
+                (
+                    mouse_position,
+                    rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT),
+                )
+            };
+            let is_cancelled = transform.kind != TransformKind::Move
+                && rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+            for (point, original_position) in self.points.iter_mut().zip(original_positions) {
+                if is_cancelled {
+                    point.set_position(*original_position, false);
+                } else if point.is_selected {
+                    point.set_position(
+                        transform.apply(*original_position, transform_position),
+                        false,
+                    );
+                }
+            }
+            if is_done || is_cancelled {
+                self.transform = None;
+            }
+        } else if let Some(start) = self.box_selection_start {
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                let rectangle = selection_rectangle(start, mouse_position);
                 for point in self.points.iter_mut() {
-                    if point.is_hovered {
+                    if rectangle_contains(rectangle, point.position) {
                         point.is_selected = true;
-                        self.has_point_selected = true;
-                        break;
+                    } else if !shift_down {
+                        point.is_selected = false;
                     }
                 }
+                self.box_selection_start = None;
             }
-        } else if self.has_point_selected {
-            for point in self.points.iter_mut() {
-                point.is_selected = false;
+        } else if rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            match self
+                .points
+                .iter()
+                .position(|p| p.is_under(mouse_position, zoom))
+            {
+                Some(index) if shift_down => {
+                    self.points[index].is_selected = !self.points[index].is_selected;
+                }
+                Some(index) => {
+                    if !self.points[index].is_selected {
+                        for (i, point) in self.points.iter_mut().enumerate() {
+                            point.is_selected = i == index;
+                        }
+                    }
+                    self.start_transform(TransformKind::Move, self.points[index].position);
+                }
+                None => self.box_selection_start = Some(mouse_position),
             }
-            self.has_point_selected = false;
         }
 
         if let Some(key) = rl_handle.get_key_pressed() {
-            if !self.has_point_selected {
+            if self.transform.is_none() && self.box_selection_start.is_none() {
                 match key {
                     KeyboardKey::KEY_SPACE => {
-                        if self.points.len() < 62 {
-                            // Prevent binomial overflow
-                            self.points.last_mut().unwrap().color = COLOR_LIGHT;
-                            let new_point = BasicPoint::new(mouse_position, COLOR_BLUE);
-                            self.points.push(new_point);
-                        }
+                        self.points.last_mut().unwrap().color = COLOR_LIGHT;
+                        let new_point = BasicPoint::new(mouse_position, COLOR_BLUE);
+                        self.points.push(new_point);
                     }
                     KeyboardKey::KEY_BACKSPACE => {
                         if self.points.len() > 2 {
@@ -82,10 +194,23 @@ impl Scene for BezierCurveScene {
                             self.points.last_mut().unwrap().color = COLOR_BLUE;
                         }
                     }
+                    KeyboardKey::KEY_R if !control_down => {
+                        self.start_transform(TransformKind::Rotate, mouse_position);
+                    }
+                    KeyboardKey::KEY_S if !control_down => {
+                        self.start_transform(TransformKind::Scale, mouse_position);
+                    }
+                    KeyboardKey::KEY_P => {
+                        self.pivot = match self.pivot {
+                            Some(_) => None,
+                            None => Some(mouse_position),
+                        };
+                    }
                     _ => {}
                 }
             }
         }
+        self.track_edit(is_wheel_edit);
 
         // Update Animation
         if self.animated {
@@ -109,8 +234,91 @@ impl Scene for BezierCurveScene {
         let right_slider_text = CStr::from_bytes_with_nul(b"1.0\0").unwrap();
         let animation_toggle_text = CStr::from_bytes_with_nul(b"Animate T value\0").unwrap();
         let debug_text = CStr::from_bytes_with_nul(b"Activate debug draw\0").unwrap();
+        let circle_arc_text = CStr::from_bytes_with_nul(b"Circle arc preset\0").unwrap();
 
-        // Draw GUI Controls
+        // Draw the curve in world space
+        {
+            let visible_area = self.camera.visible_area(
+                rl_draw_handle.get_screen_width(),
+                rl_draw_handle.get_screen_height(),
+            );
+            let mouse_position = self.camera.mouse_position(rl_draw_handle);
+            let zoom = self.camera.camera().zoom;
+            let mut rl_mode_2d = rl_draw_handle.begin_mode2D(self.camera.camera());
+            self.grid.draw(&mut rl_mode_2d, visible_area, zoom);
+            if self.debug_draw {
+                // Highlight the part of the curve already travelled by t
+                draw_bezier_line(
+                    &rational_bezier_sub_curve(&self.points, 0.0, self.t),
+                    &mut rl_mode_2d,
+                    8.0,
+                    COLOR_YELLOW,
+                    DRAW_TOLERANCE / zoom,
+                );
+            }
+            draw_bezier(
+                &self.points,
+                &mut rl_mode_2d,
+                if self.debug_draw { Some(self.t) } else { None },
+                zoom,
+            );
+            for point in self.points.iter().filter(|p| p.is_selected) {
+                draw_selected_point(&mut rl_mode_2d, point.position, zoom);
+            }
+            if let Some(start) = self.box_selection_start {
+                draw_selection_rectangle(
+                    &mut rl_mode_2d,
+                    selection_rectangle(start, mouse_position),
+                    zoom,
+                );
+            }
+            if let Some(pivot) = self.transform_pivot() {
+                draw_pivot(&mut rl_mode_2d, pivot, zoom);
+            }
+            if self.debug_draw {
+                draw_curve_frame(
+                    &mut rl_mode_2d,
+                    evalute_bezier_curve(&self.points, self.t),
+                    bezier_tangent(&self.points, self.t),
+                    bezier_normal(&self.points, self.t),
+                    zoom,
+                );
+            }
+        }
+
+        if self.debug_draw {
+            draw_curvature_label(
+                rl_draw_handle,
+                self.camera
+                    .world_to_screen(evalute_bezier_curve(&self.points, self.t)),
+                bezier_curvature(&self.points, self.t),
+            );
+        }
+
+        for point in self
+            .points
+            .iter()
+            .filter(|p| p.weight != 1.0 || p.is_hovered)
+        {
+            let screen_position = self.camera.world_to_screen(point.position);
+            rl_draw_handle.draw_text(
+                format!("w = {:.2}", point.weight).as_str(),
+                screen_position.x as i32 + 20,
+                screen_position.y as i32 - 30,
+                18,
+                COLOR_LIGHT,
+            );
+        }
+        if let Some(nearest) = self.hovered_curve_point {
+            draw_curve_hover(
+                rl_draw_handle,
+                self.camera.world_to_screen(nearest.position),
+                &format!("t = {:.3}", nearest.t),
+            );
+        }
+
+        // Draw GUI Controls (screen space)
+        self.grid.draw_gui(rl_draw_handle);
         rl_draw_handle.gui_toggle(
             Rectangle::new(40.0, 20.0, 300.0, 25.0),
             Some(debug_text),
@@ -132,11 +340,230 @@ impl Scene for BezierCurveScene {
             );
         }
 
-        draw_bezier(
-            &self.points,
-            rl_draw_handle,
-            if self.debug_draw { Some(self.t) } else { None },
+        let length = self.arc_length_table().length();
+        let length_text = if self.debug_draw {
+            let t = self.t;
+            format!(
+                "Length: {:.1} (t at {:.1})",
+                length,
+                self.arc_length_table().t_to_distance(0, t)
+            )
+        } else {
+            format!("Length: {:.1}", length)
+        };
+        rl_draw_handle.draw_text(
+            length_text.as_str(),
+            40,
+            if self.debug_draw { 110 } else { 50 },
+            18,
+            COLOR_LIGHT,
+        );
+        if rl_draw_handle.gui_button(
+            Rectangle::new(
+                40.0,
+                if self.debug_draw { 140.0 } else { 80.0 },
+                300.0,
+                25.0,
+            ),
+            Some(circle_arc_text),
+        ) {
+            // Exact third of a circle (rational quadratic curve)
+            let arc = rational_circle_arc(
+                Vector2::new(750.0, 700.0),
+                400.0,
+                7.0 * PI / 6.0,
+                2.0 * PI / 3.0,
+            );
+            self.points = arc
+                .iter()
+                .enumerate()
+                .map(|(i, (position, weight))| {
+                    let color = if i == 1 { COLOR_LIGHT } else { COLOR_BLUE };
+                    BasicPoint {
+                        weight: *weight,
+                        ..BasicPoint::new(*position, color)
+                    }
+                })
+                .collect();
+        }
+    }
+
+    fn save_file_name(&self) -> Option<&str> {
+        Some("bezier_curve.json")
+    }
+
+    fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.to_save()).map_err(|e| e.to_string())
+    }
+
+    fn load_json(&mut self, json: &str) -> Result<(), String> {
+        let save: CurveSceneSave = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        self.apply_save(save)
+    }
+
+    fn to_svg(&self) -> Result<String, String> {
+        Ok(svg_document(
+            &bezier_to_cubics(&self.points, SVG_TOLERANCE),
+            false,
+        ))
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.transform.is_some() {
+            return false;
+        }
+        self.track_edit(false);
+        let current_state = self.history_state();
+        match self.history.undo(current_state) {
+            Some(state) => self.restore_state(state),
+            None => false,
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        if self.transform.is_some() {
+            return false;
+        }
+        self.track_edit(false);
+        let current_state = self.history_state();
+        match self.history.redo(current_state) {
+            Some(state) => self.restore_state(state),
+            None => false,
+        }
+    }
+}
+impl BezierCurveScene {
+    fn to_save(&self) -> CurveSceneSave {
+        CurveSceneSave {
+            points: self
+                .points
+                .iter()
+                .map(|p| vector_to_array(p.position))
+                .collect(),
+            weights: self.points.iter().map(|p| p.weight).collect(),
+            animated: self.animated,
+            debug_draw: self.debug_draw,
+            t: self.t,
+        }
+    }
+
+    fn apply_save(&mut self, save: CurveSceneSave) -> Result<(), String> {
+        if save.points.len() < 2 {
+            return Err("A curve needs at least 2 points".to_string());
+        }
+        if save.weights.iter().any(|weight| *weight <= 0.0) {
+            return Err("Weights must be positive".to_string());
+        }
+        let point_count = save.points.len();
+        self.points = save
+            .points
+            .into_iter()
+            .enumerate()
+            .map(|(i, position)| {
+                let color = if i == 0 || i == point_count - 1 {
+                    COLOR_BLUE
+                } else {
+                    COLOR_LIGHT
+                };
+                BasicPoint {
+                    weight: save.weights.get(i).copied().unwrap_or(1.0),
+                    ..BasicPoint::new(array_to_vector(position), color)
+                }
+            })
+            .collect();
+        self.animated = save.animated;
+        self.debug_draw = save.debug_draw;
+        self.t = save.t.clamp(0.0, 1.0);
+        self.animation_bounce = false;
+        self.transform = None;
+        self.box_selection_start = None;
+        Ok(())
+    }
+
+    /// State tracked by the undo history (the value of t is not an edit)
+    fn history_state(&self) -> CurveSceneSave {
+        CurveSceneSave {
+            t: 0.0,
+            ..self.to_save()
+        }
+    }
+
+    /// Track the changes made since the last tracked state as a new edit (merged with the previous one when set)
+    /// Nothing is tracked while points are transformed so that a whole drag is a single edit. Edits made with the GUI
+    /// are tracked on the next update.
+    fn track_edit(&mut self, merge: bool) {
+        if self.transform.is_some() {
+            return;
+        }
+        self.history.track(self.history_state(), merge);
+    }
+
+    /// Arc length table of the curve, computed again when the points or their weights changed
+    fn arc_length_table(&mut self) -> &ArcLengthTable {
+        let points = self
+            .points
+            .iter()
+            .map(|p| (p.position, p.weight))
+            .collect::<Vec<_>>();
+        match &mut self.arc_length_table {
+            Some((measured_points, _)) if *measured_points == points => {}
+            cache => *cache = Some((points, ArcLengthTable::from_curve(&self.points))),
+        }
+        &self.arc_length_table.as_ref().unwrap().1
+    }
+
+    /// Point of the curve closest to the position, computed again when the points or the position changed
+    fn nearest_curve_point(&mut self, position: Vector2) -> NearestPoint {
+        let points = self
+            .points
+            .iter()
+            .map(|p| (p.position, p.weight))
+            .collect::<Vec<_>>();
+        match &mut self.nearest_curve_point {
+            Some((measured_points, measured_position, nearest))
+                if *measured_points == points && *measured_position == position =>
+            {
+                *nearest
+            }
+            cache => {
+                let nearest = nearest_point_on_bezier(&self.points, position);
+                *cache = Some((points, position, nearest));
+                nearest
+            }
+        }
+    }
+
+    /// Start transforming the selected points (nothing happens without selection)
+    fn start_transform(&mut self, kind: TransformKind, start: Vector2) {
+        let selection_center = centroid(
+            self.points
+                .iter()
+                .filter(|p| p.is_selected)
+                .map(|p| p.position),
         );
+        if let Some(center) = selection_center {
+            self.transform = Some((
+                GroupTransform::new(kind, self.pivot.unwrap_or(center), start),
+                self.points.iter().map(|p| p.position).collect(),
+            ));
+        }
+    }
+
+    /// Pivot to draw (custom pivot or pivot of the rotation or scale in progress)
+    fn transform_pivot(&self) -> Option<Vector2> {
+        match &self.transform {
+            Some((transform, _)) if transform.kind != TransformKind::Move => Some(transform.pivot),
+            _ => self.pivot,
+        }
+    }
+
+    fn restore_state(&mut self, state: CurveSceneSave) -> bool {
+        let t = self.t;
+        if self.apply_save(state).is_err() {
+            return false;
+        }
+        self.t = t;
+        true
     }
 }
 impl Default for BezierCurveScene {
@@ -160,13 +587,23 @@ impl Default for BezierCurveScene {
         })
         .collect::<Vec<_>>();
 
-        BezierCurveScene {
+        let mut scene = BezierCurveScene {
             points,
             animated: true,
             animation_bounce: false,
-            has_point_selected: false,
             debug_draw: true,
             t: 0.5,
-        }
+            transform: None,
+            box_selection_start: None,
+            pivot: None,
+            hovered_curve_point: None,
+            arc_length_table: None,
+            nearest_curve_point: None,
+            camera: SceneCamera::default(),
+            grid: Grid::default(),
+            history: History::new(HISTORY_CAPACITY),
+        };
+        scene.track_edit(false);
+        scene
     }
 }
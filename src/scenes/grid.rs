@@ -0,0 +1,172 @@
+use crate::colors::*;
+use raylib::prelude::*;
+use std::ffi::CStr;
+
+const DEFAULT_SPACING: f32 = 50.0;
+const MIN_SPACING: f32 = 10.0;
+const MAX_SPACING: f32 = 200.0;
+/// Every n-th line of the grid is a major line
+const MAJOR_LINE_EVERY: i64 = 5;
+/// Lines closer than that on screen are not drawn
+const MIN_SCREEN_SPACING: f32 = 6.0;
+
+/// World space coordinate grid with optional snapping
+pub struct Grid {
+    pub spacing: f32,
+    pub is_visible: bool,
+    pub snap: bool,
+}
+impl Grid {
+    /// Closest grid intersection
+    pub fn snap_position(&self, position: Vector2) -> Vector2 {
+        Vector2::new(
+            (position.x / self.spacing).round() * self.spacing,
+            (position.y / self.spacing).round() * self.spacing,
+        )
+    }
+
+    /// Position of a dragged point, snapped when snapping is on (the modifier temporarily inverts the setting)
+    pub fn drag_position(&self, position: Vector2, modifier_down: bool) -> Vector2 {
+        if self.snap != modifier_down {
+            self.snap_position(position)
+        } else {
+            position
+        }
+    }
+
+    /// Draw the grid lines over the visible world area
+    pub fn draw(&self, d: &mut impl RaylibDraw, visible_area: Rectangle, zoom: f32) {
+        if !self.is_visible {
+            return;
+        }
+        let thick = 1.0 / zoom;
+        let (x_min, x_max) = (visible_area.x, visible_area.x + visible_area.width);
+        let (y_min, y_max) = (visible_area.y, visible_area.y + visible_area.height);
+        let draw_minor_lines = self.spacing * zoom >= MIN_SCREEN_SPACING;
+        let draw_major_lines = self.spacing * MAJOR_LINE_EVERY as f32 * zoom >= MIN_SCREEN_SPACING;
+
+        for (index, x) in grid_lines(x_min, x_max, self.spacing) {
+            let color = match line_kind(index) {
+                LineKind::Axis => COLOR_GREEN,
+                LineKind::Major if draw_major_lines => COLOR_GRID_MAJOR,
+                LineKind::Minor if draw_minor_lines => COLOR_GRID_MINOR,
+                _ => continue,
+            };
+            d.draw_line_ex(Vector2::new(x, y_min), Vector2::new(x, y_max), thick, color);
+        }
+        for (index, y) in grid_lines(y_min, y_max, self.spacing) {
+            let color = match line_kind(index) {
+                LineKind::Axis => COLOR_RED,
+                LineKind::Major if draw_major_lines => COLOR_GRID_MAJOR,
+                LineKind::Minor if draw_minor_lines => COLOR_GRID_MINOR,
+                _ => continue,
+            };
+            d.draw_line_ex(Vector2::new(x_min, y), Vector2::new(x_max, y), thick, color);
+        }
+    }
+
+    /// Draw the grid settings in the top right corner of the screen
+    pub fn draw_gui(&mut self, rl_draw_handle: &mut RaylibDrawHandle) {
+        let grid_text = CStr::from_bytes_with_nul(b"Show grid\0").unwrap();
+        let snap_text = CStr::from_bytes_with_nul(b"Snap to grid (ALT)\0").unwrap();
+        let spacing_text = CStr::from_bytes_with_nul(b"Spacing\0").unwrap();
+
+        let x = rl_draw_handle.get_screen_width() as f32 - 340.0;
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(x, 50.0, 300.0, 25.0),
+            Some(grid_text),
+            &mut self.is_visible,
+        );
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(x, 80.0, 300.0, 25.0),
+            Some(snap_text),
+            &mut self.snap,
+        );
+        if self.is_visible || self.snap {
+            rl_draw_handle.gui_slider_bar(
+                Rectangle::new(x, 110.0, 300.0, 25.0),
+                Some(spacing_text),
+                None,
+                &mut self.spacing,
+                MIN_SPACING,
+                MAX_SPACING,
+            );
+            self.spacing = self.spacing.round();
+        }
+    }
+}
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            spacing: DEFAULT_SPACING,
+            is_visible: false,
+            snap: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LineKind {
+    Axis,
+    Major,
+    Minor,
+}
+
+fn line_kind(index: i64) -> LineKind {
+    if index == 0 {
+        LineKind::Axis
+    } else if index % MAJOR_LINE_EVERY == 0 {
+        LineKind::Major
+    } else {
+        LineKind::Minor
+    }
+}
+
+/// Index and position of every grid line between min and max
+fn grid_lines(min: f32, max: f32, spacing: f32) -> impl Iterator<Item = (i64, f32)> {
+    let first = (min / spacing).ceil() as i64;
+    let last = (max / spacing).floor() as i64;
+    (first..=last).map(move |index| (index, index as f32 * spacing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_to_closest_intersection() {
+        let grid = Grid::default();
+        assert_eq!(
+            grid.snap_position(Vector2::new(74.0, -26.0)),
+            Vector2::new(50.0, -50.0)
+        );
+        assert_eq!(
+            grid.snap_position(Vector2::new(76.0, 124.0)),
+            Vector2::new(100.0, 100.0)
+        );
+
+        let position = Vector2::new(12.0, 13.0);
+        assert_eq!(grid.drag_position(position, false), position);
+        assert_eq!(grid.drag_position(position, true), Vector2::zero());
+        let snapping_grid = Grid {
+            snap: true,
+            ..Grid::default()
+        };
+        assert_eq!(
+            snapping_grid.drag_position(position, false),
+            Vector2::zero()
+        );
+        assert_eq!(snapping_grid.drag_position(position, true), position);
+    }
+
+    #[test]
+    fn lines_cover_the_visible_area() {
+        let lines = grid_lines(-120.0, 260.0, 50.0).collect::<Vec<_>>();
+        assert_eq!(lines.first(), Some(&(-2, -100.0)));
+        assert_eq!(lines.last(), Some(&(5, 250.0)));
+        assert_eq!(lines.len(), 8);
+        assert_eq!(line_kind(0), LineKind::Axis);
+        assert_eq!(line_kind(-5), LineKind::Major);
+        assert_eq!(line_kind(3), LineKind::Minor);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+/// Number of edits that can be undone
+pub const HISTORY_CAPACITY: usize = 100;
+
+/// Bounded undo/redo stack of scene states
+/// Each edit is recorded as the state right before it. Undoing swaps the current state with the recorded one.
+pub struct History<T> {
+    undo_stack: VecDeque<T>,
+    redo_stack: Vec<T>,
+    capacity: usize,
+    /// State given to the last call of `track` (or restored by the last undo or redo)
+    tracked_state: Option<T>,
+    /// Whether the last tracked edit absorbs the next merged one
+    is_merging: bool,
+}
+impl<T: Clone + PartialEq> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::with_capacity(capacity),
+            redo_stack: Vec::new(),
+            capacity,
+            tracked_state: None,
+            is_merging: false,
+        }
+    }
+
+    /// Record the tracked state as a new edit when the current state differs from it
+    /// Consecutive merged edits (like the ticks of the mouse wheel) are a single edit. The first call only starts
+    /// tracking.
+    pub fn track(&mut self, state: T, merge: bool) {
+        if self.tracked_state.as_ref() == Some(&state) {
+            return;
+        }
+        if let Some(previous_state) = self.tracked_state.replace(state) {
+            if !(merge && self.is_merging) {
+                self.record(previous_state);
+            }
+            self.is_merging = merge;
+        }
+    }
+
+    /// Record the state before a new edit (forgets every undone edit)
+    pub fn record(&mut self, previous_state: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(previous_state);
+        self.redo_stack.clear();
+    }
+
+    /// State before the last edit, the current state is kept for redo
+    pub fn undo(&mut self, current_state: T) -> Option<T> {
+        let state = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current_state);
+        self.restore(state)
+    }
+
+    /// State after the last undone edit, the current state is kept for undo
+    pub fn redo(&mut self, current_state: T) -> Option<T> {
+        let state = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current_state);
+        self.restore(state)
+    }
+
+    /// Track the state given back by an undo or a redo (it is not an edit)
+    fn restore(&mut self, state: T) -> Option<T> {
+        self.tracked_state = Some(state.clone());
+        self.is_merging = false;
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::{BezierSplineScene, Scene};
+
+    #[test]
+    fn undo_then_redo() {
+        let mut history = History::new(10);
+        history.record(0);
+        history.record(1);
+        // Current state is 2
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.undo(0), None);
+        assert_eq!(history.redo(0), Some(1));
+        assert_eq!(history.redo(1), Some(2));
+        assert_eq!(history.redo(2), None);
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = History::new(10);
+        history.record(0);
+        assert_eq!(history.undo(1), Some(0));
+        history.record(0);
+        assert_eq!(history.redo(3), None);
+        assert_eq!(history.undo(3), Some(0));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = History::new(3);
+        for state in 0..10 {
+            history.record(state);
+        }
+        assert_eq!(history.undo(10), Some(9));
+        assert_eq!(history.undo(9), Some(8));
+        assert_eq!(history.undo(8), Some(7));
+        assert_eq!(history.undo(7), None);
+    }
+
+    #[test]
+    fn tracked_changes_are_edits() {
+        let mut history = History::new(10);
+        history.track(0, false);
+        history.track(0, false);
+        assert_eq!(history.undo(0), None);
+        history.track(1, false);
+        // Consecutive merged changes are a single edit
+        history.track(2, true);
+        history.track(2, true);
+        history.track(3, true);
+        history.track(4, false);
+        assert_eq!(history.undo(4), Some(3));
+        assert_eq!(history.undo(3), Some(1));
+        // Restored states are not edits
+        history.track(1, false);
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.redo(0), Some(1));
+        history.track(1, true);
+        history.track(5, true);
+        assert_eq!(history.redo(5), None);
+        assert_eq!(history.undo(5), Some(1));
+    }
+
+    #[test]
+    fn scene_edits_can_be_undone() {
+        let mut scene = BezierSplineScene::default();
+        let original = scene.to_json().unwrap();
+        assert!(!scene.undo());
+
+        scene.import_svg("M 0 0 C 10 10 20 10 30 0 L 60 0").unwrap();
+        let imported = scene.to_json().unwrap();
+        assert!(scene.undo());
+        assert_eq!(scene.to_json().unwrap(), original);
+        assert!(scene.redo());
+        assert_eq!(scene.to_json().unwrap(), imported);
+        assert!(!scene.redo());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::bezier::*;
+use crate::colors::*;
+use crate::scenes::camera::SceneCamera;
+use crate::scenes::grid::Grid;
+use crate::scenes::Scene;
+use raylib::prelude::*;
+use std::ffi::CStr;
+
+/// Maximum number of intersections listed on screen
+const MAX_LISTED_INTERSECTIONS: usize = 10;
+
+pub struct BezierIntersectionScene {
+    splines: [Spline; 2],
+    /// Index of the spline edited with the keyboard
+    active_spline: usize,
+    /// Spline and point being dragged
+    dragged_point: Option<(usize, SplinePointId)>,
+    intersections: Vec<(usize, usize, Intersection)>,
+    draw_bounding_box: bool,
+    camera: SceneCamera,
+    grid: Grid,
+}
+impl Scene for BezierIntersectionScene {
+    fn get_title(&self) -> &str {
+        "Bezier Intersection Scene"
+    }
+
+    fn has_background(&self) -> bool {
+        self.grid.is_visible
+    }
+
+    fn help_text(&self) -> Vec<&str> {
+        [
+            "ESC - Go back to main menu",
+            "MOUSE CLICK - Move point (the spline of the point becomes the active one)",
+            "TAB - Switch the active spline",
+            "SPACE - Add new cubic Bezier to the active spline with the last join at mouse position",
+            "BACKSPACE - Remove last cubic Bezier of the active spline",
+            "MOUSE WHEEL / MIDDLE DRAG - Zoom / Pan the view",
+            "ALT WHILE DRAGGING - Toggle snapping to the grid",
+        ]
+        .to_vec()
+    }
+
+    fn update(&mut self, rl_handle: &mut RaylibHandle) {
+        // Update inputs
+        self.camera.update(rl_handle);
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let drag_position = self.grid.drag_position(
+            mouse_position,
+            rl_handle.is_key_down(KeyboardKey::KEY_LEFT_ALT)
+                || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_ALT),
+        );
+
+        // Scene computation
+        // Update spline points
+        for spline in self.splines.iter_mut() {
+            for point_id in spline.point_ids() {
+                spline.point_mut(point_id).udpate_gui(mouse_position, zoom);
+            }
+        }
+        if let Some((spline, point_id)) = self.dragged_point {
+            self.splines[spline].move_point(point_id, drag_position, true);
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.splines[spline].point_mut(point_id).set_selected(false);
+                self.dragged_point = None;
+            }
+        } else if rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            'search: for (spline_index, spline) in self.splines.iter_mut().enumerate() {
+                for point_id in spline.point_ids() {
+                    let point = spline.point_mut(point_id);
+                    if point.is_hovered() {
+                        point.set_selected(true);
+                        self.dragged_point = Some((spline_index, point_id));
+                        self.active_spline = spline_index;
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        if let Some(key) = rl_handle.get_key_pressed() {
+            if self.dragged_point.is_none() {
+                match key {
+                    KeyboardKey::KEY_TAB => {
+                        self.active_spline = (self.active_spline + 1) % self.splines.len();
+                    }
+                    KeyboardKey::KEY_SPACE => {
+                        self.splines[self.active_spline].push_join(mouse_position);
+                    }
+                    KeyboardKey::KEY_BACKSPACE => {
+                        self.splines[self.active_spline].pop_segment();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.intersections = self.splines[0].intersections(&self.splines[1]);
+    }
+
+    fn draw(&mut self, rl_draw_handle: &mut RaylibDrawHandle) {
+        let bounding_box_toggle_text = CStr::from_bytes_with_nul(b"Draw Bouding box\0").unwrap();
+
+        // Draw the splines in world space
+        {
+            let visible_area = self.camera.visible_area(
+                rl_draw_handle.get_screen_width(),
+                rl_draw_handle.get_screen_height(),
+            );
+            let zoom = self.camera.camera().zoom;
+            let mut rl_mode_2d = rl_draw_handle.begin_mode2D(self.camera.camera());
+            self.grid.draw(&mut rl_mode_2d, visible_area, zoom);
+            for (spline_index, spline) in self.splines.iter().enumerate() {
+                for cubic_bezier_points in spline.segments() {
+                    if spline_index == self.active_spline {
+                        // Highlight the spline edited with the keyboard
+                        draw_bezier_line(
+                            &cubic_bezier_points,
+                            &mut rl_mode_2d,
+                            10.0 / zoom,
+                            COLOR_LIGHT.fade(0.2),
+                            DRAW_TOLERANCE / zoom,
+                        );
+                    }
+                    draw_bezier(&cubic_bezier_points, &mut rl_mode_2d, None, zoom);
+                    if self.draw_bounding_box {
+                        if let Ok(bb) = bezier_bounding_box(&cubic_bezier_points) {
+                            rl_mode_2d.draw_rectangle_lines_ex(bb, 1.0 / zoom, COLOR_RED);
+                        }
+                    }
+                }
+            }
+            for (_, _, intersection) in self.intersections.iter() {
+                rl_mode_2d.draw_ring(
+                    intersection.position,
+                    POINTS_RADIUS * 0.5 / zoom,
+                    (POINTS_RADIUS * 0.5 + 3.0) / zoom,
+                    0.0,
+                    360.0,
+                    32,
+                    COLOR_YELLOW,
+                );
+            }
+        }
+
+        // Draw GUI Controls (screen space)
+        self.grid.draw_gui(rl_draw_handle);
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(40.0, 20.0, 300.0, 25.0),
+            Some(bounding_box_toggle_text),
+            &mut self.draw_bounding_box,
+        );
+        rl_draw_handle.draw_text(
+            format!("Intersections: {}", self.intersections.len()).as_str(),
+            40,
+            60,
+            18,
+            COLOR_LIGHT,
+        );
+        for (i, (segment, other_segment, intersection)) in self
+            .intersections
+            .iter()
+            .take(MAX_LISTED_INTERSECTIONS)
+            .enumerate()
+        {
+            rl_draw_handle.draw_text(
+                format!(
+                    "segment {} t = {:.3} / segment {} t = {:.3}",
+                    segment, intersection.t1, other_segment, intersection.t2
+                )
+                .as_str(),
+                40,
+                90 + 25 * i as i32,
+                18,
+                COLOR_LIGHT,
+            );
+        }
+    }
+}
+impl Default for BezierIntersectionScene {
+    fn default() -> Self {
+        Self {
+            splines: [
+                Spline::new([
+                    Vector2::new(300.0, 600.0),
+                    Vector2::new(600.0, 300.0),
+                    Vector2::new(900.0, 300.0),
+                    Vector2::new(1200.0, 600.0),
+                ]),
+                Spline::new([
+                    Vector2::new(300.0, 350.0),
+                    Vector2::new(600.0, 700.0),
+                    Vector2::new(900.0, 100.0),
+                    Vector2::new(1200.0, 450.0),
+                ]),
+            ],
+            active_spline: 0,
+            dragged_point: None,
+            intersections: Vec::new(),
+            draw_bounding_box: false,
+            camera: SceneCamera::default(),
+            grid: Grid::default(),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bezier::{JoinMode, SplineJoin};
+
+/// Saved state of a `BezierCurveScene`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CurveSceneSave {
+    pub points: Vec<[f32; 2]>,
+    /// Weights of the points (all 1 when missing)
+    #[serde(default)]
+    pub weights: Vec<f32>,
+    pub animated: bool,
+    pub debug_draw: bool,
+    pub t: f32,
+}
+
+/// Saved join point of a spline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinSave {
+    pub position: [f32; 2],
+    pub previous_handle: [f32; 2],
+    pub next_handle: [f32; 2],
+    pub mode: JoinMode,
+}
+impl From<&SplineJoin> for JoinSave {
+    fn from(join: &SplineJoin) -> Self {
+        Self {
+            position: vector_to_array(join.join.position),
+            previous_handle: vector_to_array(join.previous_handle.position),
+            next_handle: vector_to_array(join.next_handle.position),
+            mode: join.mode,
+        }
+    }
+}
+impl From<&JoinSave> for SplineJoin {
+    fn from(join: &JoinSave) -> Self {
+        SplineJoin::new(
+            array_to_vector(join.position),
+            array_to_vector(join.previous_handle),
+            array_to_vector(join.next_handle),
+            join.mode,
+        )
+    }
+}
+
+/// Saved state of a `BezierSplineScene`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SplineSceneSave {
+    pub joins: Vec<JoinSave>,
+    pub is_closed_loop: bool,
+    pub animated: bool,
+    pub debug_draw: bool,
+    pub t: f32,
+    pub draw_bounding_box: bool,
+    pub lock_move: bool,
+}
+
+pub fn vector_to_array(vector: Vector2) -> [f32; 2] {
+    [vector.x, vector.y]
+}
+
+pub fn array_to_vector(array: [f32; 2]) -> Vector2 {
+    Vector2::new(array[0], array[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::{BezierCurveScene, BezierSplineScene, Scene};
+
+    #[test]
+    fn curve_scene_round_trip() {
+        let save = CurveSceneSave {
+            points: vec![[10.0, 20.0], [30.5, 40.0], [50.0, 60.25]],
+            weights: vec![1.0, 0.5, 2.0],
+            animated: false,
+            debug_draw: true,
+            t: 0.25,
+        };
+        let json = serde_json::to_string(&save).unwrap();
+
+        let mut scene = BezierCurveScene::default();
+        scene.load_json(&json).unwrap();
+        let saved_json = scene.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<CurveSceneSave>(&saved_json).unwrap(),
+            save
+        );
+
+        // Saves without weights are plain Bezier curves
+        scene
+            .load_json(
+                r#"{"points":[[0.0,0.0],[1.0,1.0]],"animated":false,"debug_draw":false,"t":0.0}"#,
+            )
+            .unwrap();
+        let saved: CurveSceneSave = serde_json::from_str(&scene.to_json().unwrap()).unwrap();
+        assert_eq!(saved.weights, vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn spline_scene_round_trip() {
+        let save = SplineSceneSave {
+            joins: vec![
+                JoinSave {
+                    position: [100.0, 100.0],
+                    previous_handle: [50.0, 100.0],
+                    next_handle: [150.0, 100.0],
+                    mode: JoinMode::Symmetric,
+                },
+                JoinSave {
+                    position: [300.0, 200.0],
+                    previous_handle: [250.0, 150.0],
+                    next_handle: [400.0, 250.0],
+                    mode: JoinMode::Corner,
+                },
+                JoinSave {
+                    position: [500.0, 100.0],
+                    previous_handle: [450.0, 120.0],
+                    next_handle: [600.0, 60.0],
+                    mode: JoinMode::Smooth,
+                },
+            ],
+            is_closed_loop: true,
+            animated: true,
+            debug_draw: false,
+            t: 0.75,
+            draw_bounding_box: true,
+            lock_move: false,
+        };
+        let json = serde_json::to_string(&save).unwrap();
+
+        let mut scene = BezierSplineScene::default();
+        scene.load_json(&json).unwrap();
+        let saved_json = scene.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<SplineSceneSave>(&saved_json).unwrap(),
+            save
+        );
+    }
+
+    #[test]
+    fn invalid_saves_are_rejected() {
+        let mut curve_scene = BezierCurveScene::default();
+        let default_json = curve_scene.to_json().unwrap();
+        assert!(curve_scene.load_json("not json").is_err());
+        assert!(curve_scene
+            .load_json(r#"{"points":[[0.0,0.0]],"animated":false,"debug_draw":false,"t":0.0}"#)
+            .is_err());
+        assert!(curve_scene
+            .load_json(r#"{"points":[[0.0,0.0],[1.0,1.0]],"weights":[1.0,-1.0],"animated":false,"debug_draw":false,"t":0.0}"#)
+            .is_err());
+        assert_eq!(curve_scene.to_json().unwrap(), default_json);
+
+        let mut spline_scene = BezierSplineScene::default();
+        assert!(spline_scene
+            .load_json(
+                &serde_json::to_string(&CurveSceneSave {
+                    points: vec![[0.0, 0.0], [1.0, 1.0]],
+                    weights: Vec::new(),
+                    animated: false,
+                    debug_draw: false,
+                    t: 0.0,
+                })
+                .unwrap()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn spline_scene_imports_svg() {
+        let mut scene = BezierSplineScene::default();
+        scene
+            .import_svg(r#"<svg><path d="M 0 0 L 100 0 Q 150 50 100 100 Z"/></svg>"#)
+            .unwrap();
+        let save: SplineSceneSave = serde_json::from_str(&scene.to_json().unwrap()).unwrap();
+        assert!(save.is_closed_loop);
+        assert_eq!(save.joins.len(), 3);
+        assert_eq!(save.joins[1].position, [100.0, 0.0]);
+
+        assert!(scene.import_svg("M 0 0").is_err());
+        assert!(scene.import_svg("<svg><path/></svg>").is_err());
+        assert_eq!(
+            serde_json::from_str::<SplineSceneSave>(&scene.to_json().unwrap()).unwrap(),
+            save
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::bezier::{POINTS_RADIUS, POINTS_RADIUS_HOVER};
+use crate::colors::*;
+use raylib::prelude::*;
+
+/// Distance (in screen pixels) under which the curve under the mouse is highlighted
+pub const CURVE_HOVER_DISTANCE: f32 = 20.0;
+
+/// Kind of transform applied to the selected points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformKind {
+    /// Follows the dragged point
+    Move,
+    /// Follows the angle of the mouse around the pivot
+    Rotate,
+    /// Follows the distance of the mouse to the pivot
+    Scale,
+}
+
+/// Transform of a group of points driven by the mouse, from the positions they had when it started
+#[derive(Debug, Clone, Copy)]
+pub struct GroupTransform {
+    pub kind: TransformKind,
+    pub pivot: Vector2,
+    /// Mouse position (or dragged point position) when the transform started
+    pub start: Vector2,
+}
+impl GroupTransform {
+    pub fn new(kind: TransformKind, pivot: Vector2, start: Vector2) -> Self {
+        Self { kind, pivot, start }
+    }
+
+    /// Transformed position of a point for the current mouse position
+    pub fn apply(&self, position: Vector2, mouse_position: Vector2) -> Vector2 {
+        match self.kind {
+            TransformKind::Move => position + (mouse_position - self.start),
+            TransformKind::Rotate => {
+                let from = self.start - self.pivot;
+                let to = mouse_position - self.pivot;
+                if from.length() < f32::EPSILON || to.length() < f32::EPSILON {
+                    return position;
+                }
+                let (sin, cos) = (from.x * to.y - from.y * to.x)
+                    .atan2(from.dot(to))
+                    .sin_cos();
+                let offset = position - self.pivot;
+                self.pivot
+                    + Vector2::new(
+                        cos * offset.x - sin * offset.y,
+                        sin * offset.x + cos * offset.y,
+                    )
+            }
+            TransformKind::Scale => {
+                let start_distance = self.start.distance_to(self.pivot);
+                if start_distance < f32::EPSILON {
+                    return position;
+                }
+                let factor = mouse_position.distance_to(self.pivot) / start_distance;
+                self.pivot + (position - self.pivot) * factor
+            }
+        }
+    }
+}
+
+/// Center of a group of points
+pub fn centroid(positions: impl Iterator<Item = Vector2>) -> Option<Vector2> {
+    let (sum, count) = positions.fold((Vector2::zero(), 0), |(sum, count), p| (sum + p, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Rectangle between two opposite corners
+pub fn selection_rectangle(corner: Vector2, opposite_corner: Vector2) -> Rectangle {
+    Rectangle::new(
+        corner.x.min(opposite_corner.x),
+        corner.y.min(opposite_corner.y),
+        (corner.x - opposite_corner.x).abs(),
+        (corner.y - opposite_corner.y).abs(),
+    )
+}
+
+pub fn rectangle_contains(rectangle: Rectangle, position: Vector2) -> bool {
+    position.x >= rectangle.x
+        && position.x <= rectangle.x + rectangle.width
+        && position.y >= rectangle.y
+        && position.y <= rectangle.y + rectangle.height
+}
+
+/// Draw the rubber band rectangle (world space)
+pub fn draw_selection_rectangle(d: &mut impl RaylibDraw, rectangle: Rectangle, zoom: f32) {
+    d.draw_rectangle_rec(rectangle, COLOR_YELLOW.fade(0.1));
+    d.draw_rectangle_lines_ex(rectangle, 1.0 / zoom, COLOR_YELLOW);
+}
+
+/// Draw the outline of a selected point (world space)
+pub fn draw_selected_point(d: &mut impl RaylibDraw, position: Vector2, zoom: f32) {
+    d.draw_ring(
+        position,
+        (POINTS_RADIUS_HOVER + 1.0) / zoom,
+        (POINTS_RADIUS_HOVER + 3.0) / zoom,
+        0.0,
+        360.0,
+        32,
+        COLOR_YELLOW,
+    );
+}
+
+/// Draw the pivot of the rotations and scales (world space)
+pub fn draw_pivot(d: &mut impl RaylibDraw, pivot: Vector2, zoom: f32) {
+    let size = 8.0 / zoom;
+    d.draw_line_ex(
+        pivot - Vector2::new(size, 0.0),
+        pivot + Vector2::new(size, 0.0),
+        2.0 / zoom,
+        COLOR_YELLOW,
+    );
+    d.draw_line_ex(
+        pivot - Vector2::new(0.0, size),
+        pivot + Vector2::new(0.0, size),
+        2.0 / zoom,
+        COLOR_YELLOW,
+    );
+}
+
+/// Draw the marker of the curve point closest to the mouse with its label (screen space)
+pub fn draw_curve_hover(d: &mut impl RaylibDraw, screen_position: Vector2, label: &str) {
+    d.draw_circle_v(screen_position, POINTS_RADIUS / 2.0, COLOR_YELLOW);
+    d.draw_text(
+        label,
+        screen_position.x as i32 + 12,
+        screen_position.y as i32 - 28,
+        18,
+        COLOR_YELLOW,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector2, b: Vector2) {
+        assert!(a.distance_to(b) < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn move_follows_the_mouse() {
+        let transform = GroupTransform::new(
+            TransformKind::Move,
+            Vector2::zero(),
+            Vector2::new(10.0, 10.0),
+        );
+        assert_close(
+            transform.apply(Vector2::new(50.0, 0.0), Vector2::new(15.0, 30.0)),
+            Vector2::new(55.0, 20.0),
+        );
+    }
+
+    #[test]
+    fn rotate_around_pivot() {
+        let pivot = Vector2::new(100.0, 100.0);
+        let transform =
+            GroupTransform::new(TransformKind::Rotate, pivot, Vector2::new(200.0, 100.0));
+        // Mouse a quarter turn away from the start
+        let mouse_position = Vector2::new(100.0, 300.0);
+        assert_close(
+            transform.apply(Vector2::new(150.0, 100.0), mouse_position),
+            Vector2::new(100.0, 150.0),
+        );
+        assert_close(transform.apply(pivot, mouse_position), pivot);
+    }
+
+    #[test]
+    fn scale_around_pivot() {
+        let pivot = Vector2::new(100.0, 100.0);
+        let transform =
+            GroupTransform::new(TransformKind::Scale, pivot, Vector2::new(110.0, 100.0));
+        assert_close(
+            transform.apply(Vector2::new(120.0, 90.0), Vector2::new(100.0, 130.0)),
+            Vector2::new(160.0, 70.0),
+        );
+        // Degenerate start keeps the points in place
+        let degenerate = GroupTransform::new(TransformKind::Scale, pivot, pivot);
+        assert_close(
+            degenerate.apply(Vector2::new(1.0, 2.0), Vector2::zero()),
+            Vector2::new(1.0, 2.0),
+        );
+    }
+
+    #[test]
+    fn rectangle_selection() {
+        let rectangle = selection_rectangle(Vector2::new(50.0, 10.0), Vector2::new(10.0, 40.0));
+        assert_eq!(rectangle.x, 10.0);
+        assert_eq!(rectangle.y, 10.0);
+        assert_eq!(rectangle.width, 40.0);
+        assert_eq!(rectangle.height, 30.0);
+        assert!(rectangle_contains(rectangle, Vector2::new(20.0, 20.0)));
+        assert!(!rectangle_contains(rectangle, Vector2::new(60.0, 20.0)));
+        assert_eq!(
+            centroid([Vector2::new(0.0, 0.0), Vector2::new(10.0, 20.0)].into_iter()),
+            Some(Vector2::new(5.0, 10.0))
+        );
+        assert_eq!(centroid(std::iter::empty()), None);
+    }
+}
@@ -1,23 +1,79 @@
 use crate::bezier::*;
 use crate::colors::*;
+use crate::scenes::camera::SceneCamera;
+use crate::scenes::grid::Grid;
+use crate::scenes::history::{History, HISTORY_CAPACITY};
+use crate::scenes::save::{JoinSave, SplineSceneSave};
+use crate::scenes::selection::*;
 use crate::scenes::Scene;
+use crate::svg::{parse_svg_path, svg_document, svg_document_path_data};
 use raylib::prelude::*;
-use std::cell::RefCell;
 use std::ffi::CStr;
-use std::rc::Rc;
 
 const T_ANIMATION_SPEED: f32 = 0.005;
+const LINE_MODE_TEXTS: [(LineKind, &[u8]); 3] = [
+    (LineKind::Line, b"Line\0"),
+    (LineKind::Ray, b"Ray\0"),
+    (LineKind::Segment, b"Segment\0"),
+];
+const STROKE_JOIN_TEXTS: [(StrokeJoin, &[u8]); 3] = [
+    (StrokeJoin::Miter, b"Miter\0"),
+    (StrokeJoin::Round, b"Round\0"),
+    (StrokeJoin::Bevel, b"Bevel\0"),
+];
+const STROKE_CAP_TEXTS: [(StrokeCap, &[u8]); 3] = [
+    (StrokeCap::Butt, b"Butt\0"),
+    (StrokeCap::Round, b"Round\0"),
+    (StrokeCap::Square, b"Square\0"),
+];
+/// Maximum distance between the stroke outline and the actual offset of the spline
+const OUTLINE_TOLERANCE: f32 = 0.5;
+
+/// Cubic segments of a spline and whether it is closed, what the curves computed from it depend on
+type SplineGeometry = (Vec<[Vector2; 4]>, bool);
 
 pub struct BezierSplineScene {
-    points: Vec<Rc<RefCell<Box<dyn MovableGuiPoint>>>>,
+    spline: Spline,
     animated: bool,
     animation_bounce: bool,
-    has_point_selected: bool,
     debug_draw: bool,
     t: f32,
     draw_bounding_box: bool,
     lock_move: bool,
-    is_closed_loop: bool,
+    /// Index of the last join point clicked
+    active_join_point: Option<usize>,
+    /// Transform of the selected points in progress with the spline as it was when it started
+    transform: Option<(GroupTransform, Spline)>,
+    /// Corner of the rubber band rectangle being dragged
+    box_selection_start: Option<Vector2>,
+    /// Pivot of the rotations and scales (the center of the selection when not set)
+    pivot: Option<Vector2>,
+    /// Segment and point of the spline closest to the mouse (when close enough)
+    hovered_curve_point: Option<(usize, NearestPoint)>,
+    show_line: bool,
+    /// Draggable end points of the line intersected with the spline
+    line_points: [BasicPoint; 2],
+    line_kind: LineKind,
+    /// Index of the line point being dragged
+    dragged_line_point: Option<usize>,
+    line_intersections: Vec<(usize, LineIntersection)>,
+    /// Line the line intersections were computed for (None when the line is hidden)
+    intersected_line: Option<Line>,
+    /// Points where the spline crosses itself
+    self_intersections: Vec<(usize, usize, Intersection)>,
+    /// Geometry of the spline the intersections, the outline and the arc length table were computed for (they are
+    /// only computed again after an edit)
+    computed_geometry: Option<SplineGeometry>,
+    show_outline: bool,
+    stroke_style: StrokeStyle,
+    /// Closed splines around the stroke of the spline (the outer contour first for a loop)
+    outlines: Vec<Spline>,
+    /// Style the outline was computed with (None when the outline is hidden)
+    outline_style: Option<StrokeStyle>,
+    arc_length_table: ArcLengthTable,
+    camera: SceneCamera,
+    grid: Grid,
+    history: History<SplineSceneSave>,
 }
 impl Scene for BezierSplineScene {
     fn get_title(&self) -> &str {
@@ -25,7 +81,7 @@ impl Scene for BezierSplineScene {
     }
 
     fn has_background(&self) -> bool {
-        false
+        self.grid.is_visible
     }
 
     fn help_text(&self) -> Vec<&str> {
@@ -33,157 +89,210 @@ impl Scene for BezierSplineScene {
             "ESC - Go back to main menu",
             "SPACE - Add new cubic Bezier to the spline with the last join at mouse position",
             "BACKSPACE - Remove last cubic Bezier set",
-            "MOUSE CLICK - Move point",
+            "MOUSE CLICK - Select and move points",
+            "SHIFT+CLICK - Add or remove a point from the selection",
+            "MOUSE DRAG ON EMPTY SPACE - Box select (SHIFT to add to the selection)",
+            "R / S - Rotate / Scale the selection with the mouse (click to apply, right click to cancel)",
+            "P - Set the pivot on mouse position (again to use the center of the selection)",
+            "MOUSE WHEEL / MIDDLE DRAG - Zoom / Pan the view",
+            "ALT WHILE DRAGGING - Toggle snapping to the grid",
+            "CTRL+Z / CTRL+SHIFT+Z - Undo / Redo",
+            "CTRL+CLICK ON CURVE - Insert a join point on the segment (a plain click starts a box selection)",
+            "GUI ON THE RIGHT - Intersect the spline with a draggable line, ray or segment",
+            "GUI ON THE RIGHT - Show the stroke outline of the spline and convert it (or one of its contours) to a closed spline",
+            "DELETE - Remove the last clicked join point",
+            "M - Cycle the mode of the last clicked join point (corner, smooth, symmetric)",
             "ENTER - Close path (while close SPACE can no longer be use)",
+            "CTRL+S - Save the spline to bezier_spline.json",
+            "CTRL+O - Load the spline from bezier_spline.json",
+            "CTRL+E - Export the spline to bezier_spline.svg",
+            "CTRL+I - Import the first path of bezier_spline.svg",
         ]
         .to_vec()
     }
 
     fn update(&mut self, rl_handle: &mut RaylibHandle) {
         // Update inputs
-        let mouse_position = Vector2::new(
-            rl_handle.get_mouse_x() as f32,
-            rl_handle.get_mouse_y() as f32,
+        self.camera.update(rl_handle);
+        let mouse_position = self.camera.mouse_position(rl_handle);
+        let zoom = self.camera.camera().zoom;
+        let drag_position = self.grid.drag_position(
+            mouse_position,
+            rl_handle.is_key_down(KeyboardKey::KEY_LEFT_ALT)
+                || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_ALT),
         );
+        let shift_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        let control_down = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
 
         // Scene computation
-        // Update self.points
-        for point in self.points.iter_mut() {
-            point.borrow_mut().udpate_gui(mouse_position);
-            if point.borrow().is_selected() {
-                point
-                    .borrow_mut()
-                    .set_position(mouse_position, self.lock_move);
+        // Update spline points
+        for point_id in self.spline.point_ids() {
+            self.spline
+                .point_mut(point_id)
+                .udpate_gui(mouse_position, zoom);
+        }
+        let mut clicked_line_point = None;
+        if self.show_line {
+            for (i, point) in self.line_points.iter_mut().enumerate() {
+                point.udpate_gui(mouse_position, zoom);
+                if point.is_under(mouse_position, zoom) && clicked_line_point.is_none() {
+                    clicked_line_point = Some(i);
+                }
             }
         }
-        if rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
-            if !self.has_point_selected {
-                for point in self.points.iter_mut() {
-                    if point.borrow().is_hovered() {
-                        point.borrow_mut().set_selected(true);
-                        self.has_point_selected = true;
-                        break;
+        if !rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            clicked_line_point = None;
+        }
+        self.hovered_curve_point = None;
+        if self.transform.is_none()
+            && self.box_selection_start.is_none()
+            && self.dragged_line_point.is_none()
+            && !self
+                .spline
+                .point_ids()
+                .any(|point_id| self.spline.point(point_id).is_under(mouse_position, zoom))
+        {
+            self.hovered_curve_point = self
+                .spline
+                .nearest_point(mouse_position)
+                .filter(|(_, nearest)| nearest.distance * zoom < CURVE_HOVER_DISTANCE);
+        }
+        if let Some(index) = self.dragged_line_point {
+            self.line_points[index].set_position(drag_position, false);
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                self.line_points[index].is_selected = false;
+                self.dragged_line_point = None;
+            }
+        } else if let Some((transform, original_spline)) = &self.transform {
+            // Moves follow the (snapped) dragged point, rotations and scales follow the mouse
+            let (transform_position, is_done) = if transform.kind == TransformKind::Move {
+                (
+                    drag_position,
+                    !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
+                )
+            } else {
+                (
+                    mouse_position,
+                    rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT),
+                )
+            };
+            let is_cancelled = transform.kind != TransformKind::Move
+                && rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+            if is_cancelled {
+                self.spline = original_spline.clone();
+            } else {
+                let selected_ids = self.selected_point_ids();
+                self.spline.transform_points(
+                    original_spline,
+                    &selected_ids,
+                    |position| transform.apply(position, transform_position),
+                    self.lock_move,
+                );
+            }
+            if is_done || is_cancelled {
+                self.transform = None;
+            }
+        } else if let Some(start) = self.box_selection_start {
+            if !rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                let rectangle = selection_rectangle(start, mouse_position);
+                for point_id in self.spline.point_ids() {
+                    let point = self.spline.point_mut(point_id);
+                    if rectangle_contains(rectangle, point.position) {
+                        point.set_selected(true);
+                    } else if !shift_down {
+                        point.set_selected(false);
                     }
                 }
+                self.box_selection_start = None;
             }
-        } else if self.has_point_selected {
-            for point in self.points.iter_mut() {
-                point.borrow_mut().set_selected(false);
+        } else if let Some(index) = clicked_line_point {
+            self.line_points[index].is_selected = true;
+            self.dragged_line_point = Some(index);
+        } else if rl_handle.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            let clicked_point = self
+                .spline
+                .point_ids()
+                .find(|point_id| self.spline.point(*point_id).is_under(mouse_position, zoom));
+            match clicked_point {
+                Some(point_id) if shift_down => {
+                    let point = self.spline.point_mut(point_id);
+                    point.set_selected(!point.is_selected());
+                }
+                Some(point_id) => {
+                    if !self.spline.point(point_id).is_selected() {
+                        for id in self.spline.point_ids() {
+                            self.spline.point_mut(id).set_selected(id == point_id);
+                        }
+                    }
+                    self.active_join_point =
+                        (point_id.kind == SplinePointKind::Join).then_some(point_id.join);
+                    self.start_transform(TransformKind::Move, self.spline.point(point_id).position);
+                }
+                None => {
+                    // CTRL+clicking on a segment (outside of any point) splits it with a new join point, any other
+                    // click starts a box selection (which deselects everything when the box is empty)
+                    match self.hovered_curve_point {
+                        Some((segment, nearest)) if control_down => {
+                            self.spline.insert_join(segment, nearest.t);
+                            for point_id in self.spline.point_ids() {
+                                self.spline.point_mut(point_id).set_selected(false);
+                            }
+                            self.active_join_point = None;
+                        }
+                        _ => self.box_selection_start = Some(mouse_position),
+                    }
+                }
             }
-            self.has_point_selected = false;
         }
 
         if let Some(key) = rl_handle.get_key_pressed() {
-            if !self.has_point_selected {
+            if self.transform.is_none()
+                && self.box_selection_start.is_none()
+                && self.dragged_line_point.is_none()
+            {
                 match key {
                     KeyboardKey::KEY_SPACE => {
-                        if !self.is_closed_loop {
-                            let pm1_pos =
-                                self.points[self.points.len() - 1].borrow().get_position();
-                            let pm2_pos =
-                                self.points[self.points.len() - 2].borrow().get_position();
-                            // First control point is mirrored with n-1 control point
-                            let new_point = RefCell::new(Box::new(ControlPoint::new(
-                                pm1_pos * 2.0 - pm2_pos,
-                                Some(&self.points[self.points.len() - 2]),
-                                Some(&self.points[self.points.len() - 1]),
-                            )));
-                            self.points.push(Rc::new(new_point));
-                            // Update next control point ref on previous join
-                            self.points[self.points.len() - 2]
-                                .borrow_mut()
-                                .set_constraint(
-                                    JoinPointConstraintID::NextControlPoint as usize,
-                                    &self.points[self.points.len() - 1],
-                                );
-                            self.points[self.points.len() - 3]
-                                .borrow_mut()
-                                .set_constraint(
-                                    ControlPointConstraintID::LinkedControlPoint as usize,
-                                    &self.points[self.points.len() - 1],
-                                );
-
-                            // Second control point between new point and last (n) control
-                            self.points
-                                .push(Rc::new(RefCell::new(Box::new(ControlPoint::new(
-                                    (mouse_position + pm1_pos) * 0.5,
-                                    None,
-                                    None,
-                                )))));
-                            self.points
-                                .push(Rc::new(RefCell::new(Box::new(JoinPoint::new(
-                                    mouse_position,
-                                    Some(&self.points[self.points.len() - 1]),
-                                    None,
-                                )))));
-                            let linked_join_point_ref = &self.points[self.points.len() - 1];
-                            self.points[self.points.len() - 2]
-                                .borrow_mut()
-                                .set_constraint(
-                                    ControlPointConstraintID::MirrorJoinPoint as usize,
-                                    linked_join_point_ref,
-                                );
-                        }
+                        self.spline.push_join(mouse_position);
                     }
                     KeyboardKey::KEY_BACKSPACE => {
-                        if self.points.len() > 4 {
-                            for _ in 0..if self.is_closed_loop { 2 } else { 3 } {
-                                self.points.pop();
-                            }
-                            self.is_closed_loop = false;
+                        if self.spline.pop_segment() {
+                            self.active_join_point = None;
                         }
                     }
-                    KeyboardKey::KEY_ENTER => {
-                        if !self.is_closed_loop {
-                            let pm1_pos =
-                                self.points[self.points.len() - 1].borrow().get_position();
-                            let pm2_pos =
-                                self.points[self.points.len() - 2].borrow().get_position();
-                            // First control point is mirrored with n-1 control point
-                            let new_point = RefCell::new(Box::new(ControlPoint::new(
-                                pm1_pos * 2.0 - pm2_pos,
-                                Some(&self.points[self.points.len() - 2]),
-                                Some(&self.points[self.points.len() - 1]),
-                            )));
-                            self.points.push(Rc::new(new_point));
-                            // Update next control point ref on previous join
-                            self.points[self.points.len() - 2]
-                                .borrow_mut()
-                                .set_constraint(
-                                    JoinPointConstraintID::NextControlPoint as usize,
-                                    &self.points[self.points.len() - 1],
-                                );
-                            self.points[self.points.len() - 3]
-                                .borrow_mut()
-                                .set_constraint(
-                                    ControlPointConstraintID::LinkedControlPoint as usize,
-                                    &self.points[self.points.len() - 1],
-                                );
-
-                            let pm1_pos = self.points[0].borrow().get_position();
-                            let pm2_pos = self.points[1].borrow().get_position();
-                            // Second control point is mirrored with first control point (arr[1])
-                            self.points
-                                .push(Rc::new(RefCell::new(Box::new(ControlPoint::new(
-                                    pm1_pos * 2.0 - pm2_pos,
-                                    Some(&self.points[1]),
-                                    Some(&self.points[0]),
-                                )))));
-                            self.points[0].borrow_mut().set_constraint(
-                                JoinPointConstraintID::PreviousControlPoint as usize,
-                                &self.points[self.points.len() - 1],
-                            );
-                            self.points[1].borrow_mut().set_constraint(
-                                ControlPointConstraintID::LinkedControlPoint as usize,
-                                &self.points[self.points.len() - 1],
-                            );
-                            self.is_closed_loop = true;
+                    KeyboardKey::KEY_M => {
+                        if let Some(join_index) = self.active_join_point {
+                            let join_mode = self.spline.joins()[join_index].mode;
+                            self.spline.set_join_mode(join_index, join_mode.next());
                         }
                     }
+                    KeyboardKey::KEY_DELETE => {
+                        if let Some(join_index) = self.active_join_point.take() {
+                            self.spline.remove_join(join_index);
+                        }
+                    }
+                    KeyboardKey::KEY_ENTER => {
+                        self.spline.close_loop();
+                    }
+                    KeyboardKey::KEY_R if !control_down => {
+                        self.start_transform(TransformKind::Rotate, mouse_position);
+                    }
+                    KeyboardKey::KEY_S if !control_down => {
+                        self.start_transform(TransformKind::Scale, mouse_position);
+                    }
+                    KeyboardKey::KEY_P => {
+                        self.pivot = match self.pivot {
+                            Some(_) => None,
+                            None => Some(mouse_position),
+                        };
+                    }
                     _ => {}
                 }
             }
         }
+        self.update_computed_curves();
+        self.track_edit(false);
 
         // Update Animation
         if self.animated {
@@ -209,8 +318,290 @@ impl Scene for BezierSplineScene {
         let bounding_box_toggle_text = CStr::from_bytes_with_nul(b"Draw Bouding box\0").unwrap();
         let debug_text = CStr::from_bytes_with_nul(b"Activate debug draw\0").unwrap();
         let lock_move_text = CStr::from_bytes_with_nul(b"Lock points\0").unwrap();
+        let corner_mode_text = CStr::from_bytes_with_nul(b"Corner\0").unwrap();
+        let smooth_mode_text = CStr::from_bytes_with_nul(b"Smooth\0").unwrap();
+        let symmetric_mode_text = CStr::from_bytes_with_nul(b"Symmetric\0").unwrap();
+        let line_toggle_text = CStr::from_bytes_with_nul(b"Intersect with a line\0").unwrap();
+        let outline_toggle_text = CStr::from_bytes_with_nul(b"Stroke outline\0").unwrap();
+        let width_slider_text = CStr::from_bytes_with_nul(b"Width\0").unwrap();
+        let convert_button_text = CStr::from_bytes_with_nul(b"Convert to outline\0").unwrap();
+        let convert_outer_button_text =
+            CStr::from_bytes_with_nul(b"Convert outer contour\0").unwrap();
+        let convert_inner_button_text =
+            CStr::from_bytes_with_nul(b"Convert inner contour\0").unwrap();
+        // Undo, redo and loads happen between the update and the draw
+        self.update_computed_curves();
+
+        // Draw the spline in world space
+        {
+            let visible_area = self.camera.visible_area(
+                rl_draw_handle.get_screen_width(),
+                rl_draw_handle.get_screen_height(),
+            );
+            let mouse_position = self.camera.mouse_position(rl_draw_handle);
+            let zoom = self.camera.camera().zoom;
+            let mut rl_mode_2d = rl_draw_handle.begin_mode2D(self.camera.camera());
+            self.grid.draw(&mut rl_mode_2d, visible_area, zoom);
+            for cubic_bezier_points in self.spline.segments() {
+                draw_bezier(
+                    &cubic_bezier_points,
+                    &mut rl_mode_2d,
+                    if self.debug_draw { Some(self.t) } else { None },
+                    zoom,
+                );
+                if self.draw_bounding_box {
+                    if let Ok(bb) = bezier_bounding_box(&cubic_bezier_points) {
+                        rl_mode_2d.draw_line_v(
+                            Vector2::new(bb.x, bb.y),
+                            Vector2::new(bb.x + bb.width, bb.y),
+                            COLOR_RED,
+                        );
+                        rl_mode_2d.draw_line_v(
+                            Vector2::new(bb.x + bb.width, bb.y),
+                            Vector2::new(bb.x + bb.width, bb.y + bb.height),
+                            COLOR_RED,
+                        );
+                        rl_mode_2d.draw_line_v(
+                            Vector2::new(bb.x + bb.width, bb.y + bb.height),
+                            Vector2::new(bb.x, bb.y + bb.height),
+                            COLOR_RED,
+                        );
+                        rl_mode_2d.draw_line_v(
+                            Vector2::new(bb.x, bb.y + bb.height),
+                            Vector2::new(bb.x, bb.y),
+                            COLOR_RED,
+                        );
+                    }
+                }
+            }
+            for outline in self.outlines.iter() {
+                for cubic_bezier_points in outline.segments() {
+                    draw_bezier_line(
+                        &cubic_bezier_points,
+                        &mut rl_mode_2d,
+                        2.0 / zoom,
+                        COLOR_GREEN,
+                        DRAW_TOLERANCE / zoom,
+                    );
+                }
+            }
+            for point_id in self.selected_point_ids() {
+                draw_selected_point(&mut rl_mode_2d, self.spline.point(point_id).position, zoom);
+            }
+            if let Some(start) = self.box_selection_start {
+                draw_selection_rectangle(
+                    &mut rl_mode_2d,
+                    selection_rectangle(start, mouse_position),
+                    zoom,
+                );
+            }
+            if let Some(pivot) = self.transform_pivot() {
+                draw_pivot(&mut rl_mode_2d, pivot, zoom);
+            }
+            if self.show_line {
+                let line = self.line();
+                let direction = (line.end - line.start).normalized();
+                // Long enough to cross the whole visible area
+                let reach = visible_area.width
+                    + visible_area.height
+                    + line
+                        .start
+                        .distance_to(Vector2::new(visible_area.x, visible_area.y));
+                let (from, to) = match line.kind {
+                    LineKind::Line => (
+                        line.start - direction * reach,
+                        line.start + direction * reach,
+                    ),
+                    LineKind::Ray => (line.start, line.start + direction * reach),
+                    LineKind::Segment => (line.start, line.end),
+                };
+                rl_mode_2d.draw_line_ex(from, to, 2.0 / zoom, COLOR_LIGHT);
+                for point in self.line_points.iter() {
+                    point.draw(&mut rl_mode_2d, zoom);
+                }
+                for (_, intersection) in self.line_intersections.iter() {
+                    rl_mode_2d.draw_ring(
+                        intersection.position,
+                        POINTS_RADIUS * 0.5 / zoom,
+                        (POINTS_RADIUS * 0.5 + 3.0) / zoom,
+                        0.0,
+                        360.0,
+                        32,
+                        COLOR_YELLOW,
+                    );
+                }
+            }
+            for (_, _, intersection) in self.self_intersections.iter() {
+                rl_mode_2d.draw_ring(
+                    intersection.position,
+                    POINTS_RADIUS * 0.5 / zoom,
+                    (POINTS_RADIUS * 0.5 + 3.0) / zoom,
+                    0.0,
+                    360.0,
+                    32,
+                    COLOR_RED,
+                );
+            }
+            if let Some(join_index) = self.active_join_point {
+                rl_mode_2d.draw_ring(
+                    self.spline.joins()[join_index].join.position,
+                    (POINTS_RADIUS_HOVER + 2.0) / zoom,
+                    (POINTS_RADIUS_HOVER + 5.0) / zoom,
+                    0.0,
+                    360.0,
+                    32,
+                    COLOR_YELLOW,
+                );
+            }
+
+            if self.debug_draw {
+                // Point moving at constant speed along the whole spline
+                let (segment, t) = self
+                    .arc_length_table
+                    .distance_to_t(self.t * self.arc_length_table.length());
+                if let Some(points) = self.spline.segment(segment) {
+                    rl_mode_2d.draw_circle_v(
+                        evalute_bezier_curve(&points, t),
+                        POINTS_RADIUS * 0.75 / zoom,
+                        COLOR_BLUE,
+                    );
+                }
+
+                for segment in 0..self.spline.segment_count() {
+                    draw_curve_frame(
+                        &mut rl_mode_2d,
+                        evalute_bezier_curve(&self.spline.segment(segment).unwrap(), self.t),
+                        self.spline.segment_tangent(segment, self.t).unwrap(),
+                        self.spline.segment_normal(segment, self.t).unwrap(),
+                        zoom,
+                    );
+                }
+            }
+        }
+
+        if self.debug_draw {
+            for segment in 0..self.spline.segment_count() {
+                draw_curvature_label(
+                    rl_draw_handle,
+                    self.camera.world_to_screen(evalute_bezier_curve(
+                        &self.spline.segment(segment).unwrap(),
+                        self.t,
+                    )),
+                    self.spline.segment_curvature(segment, self.t).unwrap(),
+                );
+            }
+        }
+
+        if let Some((segment, nearest)) = self.hovered_curve_point {
+            draw_curve_hover(
+                rl_draw_handle,
+                self.camera.world_to_screen(nearest.position),
+                &format!("segment {}, t = {:.3}", segment, nearest.t),
+            );
+        }
 
-        // Draw GUI Controls
+        // Draw GUI Controls (screen space)
+        self.grid.draw_gui(rl_draw_handle);
+        let line_gui_x = rl_draw_handle.get_screen_width() as f32 - 340.0;
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(line_gui_x, 150.0, 300.0, 25.0),
+            Some(line_toggle_text),
+            &mut self.show_line,
+        );
+        if self.show_line {
+            for (i, (kind, text)) in LINE_MODE_TEXTS.into_iter().enumerate() {
+                let mut is_active = self.line_kind == kind;
+                rl_draw_handle.gui_toggle(
+                    Rectangle::new(line_gui_x + 102.0 * i as f32, 180.0, 96.0, 25.0),
+                    Some(CStr::from_bytes_with_nul(text).unwrap()),
+                    &mut is_active,
+                );
+                if is_active {
+                    self.line_kind = kind;
+                }
+            }
+            rl_draw_handle.draw_text(
+                format!("Line intersections: {}", self.line_intersections.len()).as_str(),
+                line_gui_x as i32,
+                215,
+                18,
+                COLOR_LIGHT,
+            );
+        }
+        if !self.self_intersections.is_empty() {
+            rl_draw_handle.draw_text(
+                format!("Self intersections: {}", self.self_intersections.len()).as_str(),
+                line_gui_x as i32,
+                245,
+                18,
+                COLOR_RED,
+            );
+        }
+        rl_draw_handle.gui_toggle(
+            Rectangle::new(line_gui_x, 275.0, 300.0, 25.0),
+            Some(outline_toggle_text),
+            &mut self.show_outline,
+        );
+        if self.show_outline {
+            rl_draw_handle.gui_slider_bar(
+                Rectangle::new(line_gui_x + 60.0, 305.0, 240.0, 25.0),
+                Some(width_slider_text),
+                None,
+                &mut self.stroke_style.width,
+                2.0,
+                200.0,
+            );
+            for (i, (join, text)) in STROKE_JOIN_TEXTS.into_iter().enumerate() {
+                let mut is_active = self.stroke_style.join == join;
+                rl_draw_handle.gui_toggle(
+                    Rectangle::new(line_gui_x + 102.0 * i as f32, 335.0, 96.0, 25.0),
+                    Some(CStr::from_bytes_with_nul(text).unwrap()),
+                    &mut is_active,
+                );
+                if is_active {
+                    self.stroke_style.join = join;
+                }
+            }
+            // Caps only end open splines
+            if !self.spline.is_closed_loop() {
+                for (i, (cap, text)) in STROKE_CAP_TEXTS.into_iter().enumerate() {
+                    let mut is_active = self.stroke_style.cap == cap;
+                    rl_draw_handle.gui_toggle(
+                        Rectangle::new(line_gui_x + 102.0 * i as f32, 365.0, 96.0, 25.0),
+                        Some(CStr::from_bytes_with_nul(text).unwrap()),
+                        &mut is_active,
+                    );
+                    if is_active {
+                        self.stroke_style.cap = cap;
+                    }
+                }
+            }
+            // The outline of a closed loop has a contour on each side and a spline can only hold one of them
+            let convert_buttons = match self.outlines.len() {
+                1 => vec![(
+                    Rectangle::new(line_gui_x, 395.0, 300.0, 25.0),
+                    convert_button_text,
+                )],
+                _ => vec![
+                    (
+                        Rectangle::new(line_gui_x, 395.0, 148.0, 25.0),
+                        convert_outer_button_text,
+                    ),
+                    (
+                        Rectangle::new(line_gui_x + 152.0, 395.0, 148.0, 25.0),
+                        convert_inner_button_text,
+                    ),
+                ],
+            };
+            for (outline, (bounds, text)) in self.outlines.iter().zip(convert_buttons) {
+                if rl_draw_handle.gui_button(bounds, Some(text)) {
+                    self.spline = outline.clone();
+                    self.show_outline = false;
+                    self.active_join_point = None;
+                    break;
+                }
+            }
+        }
         rl_draw_handle.gui_toggle(
             Rectangle::new(40.0, 20.0, 300.0, 25.0),
             Some(debug_text),
@@ -246,127 +637,298 @@ impl Scene for BezierSplineScene {
             Some(lock_move_text),
             &mut self.lock_move,
         );
-
-        for cubic_bezier_points in self.points.windows(4).step_by(3) {
-            let cubic_bezier_points = cubic_bezier_points
-                .iter()
-                .map(|b| b.borrow().downcast_basic_point())
-                .collect::<Vec<_>>();
-            draw_bezier(
-                &cubic_bezier_points,
-                rl_draw_handle,
-                if self.debug_draw { Some(self.t) } else { None },
-            );
-            if self.draw_bounding_box {
-                if let Ok(bb) = cubic_bezier_bounding_box(&cubic_bezier_points) {
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x, bb.y),
-                        Vector2::new(bb.x + bb.width, bb.y),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x + bb.width, bb.y),
-                        Vector2::new(bb.x + bb.width, bb.y + bb.height),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x + bb.width, bb.y + bb.height),
-                        Vector2::new(bb.x, bb.y + bb.height),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x, bb.y + bb.height),
-                        Vector2::new(bb.x, bb.y),
-                        COLOR_RED,
-                    );
+        if let Some(join_index) = self.active_join_point {
+            let join_mode = self.spline.joins()[join_index].mode;
+            let modes_y = if self.debug_draw { 200.0 } else { 110.0 };
+            for (i, (mode, text)) in [
+                (JoinMode::Corner, corner_mode_text),
+                (JoinMode::Smooth, smooth_mode_text),
+                (JoinMode::Symmetric, symmetric_mode_text),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let mut is_active = join_mode == mode;
+                rl_draw_handle.gui_toggle(
+                    Rectangle::new(40.0 + 102.0 * i as f32, modes_y, 96.0, 25.0),
+                    Some(text),
+                    &mut is_active,
+                );
+                if is_active && join_mode != mode {
+                    self.spline.set_join_mode(join_index, mode);
                 }
             }
         }
-        if self.is_closed_loop {
-            let cubic_bezier_points = [
-                &self.points[self.points.len() - 3],
-                &self.points[self.points.len() - 2],
-                &self.points[self.points.len() - 1],
-                &self.points[0],
-            ]
-            .iter()
-            .map(|b| b.borrow().downcast_basic_point())
+
+        rl_draw_handle.draw_text(
+            format!("Length: {:.1}", self.arc_length_table.length()).as_str(),
+            40,
+            if self.debug_draw { 170 } else { 80 },
+            18,
+            COLOR_LIGHT,
+        );
+    }
+
+    fn save_file_name(&self) -> Option<&str> {
+        Some("bezier_spline.json")
+    }
+
+    fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.to_save()).map_err(|e| e.to_string())
+    }
+
+    fn load_json(&mut self, json: &str) -> Result<(), String> {
+        let save: SplineSceneSave = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        self.apply_save(save)
+    }
+
+    fn to_svg(&self) -> Result<String, String> {
+        let cubics = self
+            .spline
+            .segments()
+            .map(|segment| segment.map(|p| p.position))
             .collect::<Vec<_>>();
-            draw_bezier(
-                &cubic_bezier_points,
-                rl_draw_handle,
-                if self.debug_draw { Some(self.t) } else { None },
-            );
+        Ok(svg_document(&cubics, self.spline.is_closed_loop()))
+    }
 
-            if self.draw_bounding_box {
-                if let Ok(bb) = cubic_bezier_bounding_box(&cubic_bezier_points) {
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x, bb.y),
-                        Vector2::new(bb.x + bb.width, bb.y),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x + bb.width, bb.y),
-                        Vector2::new(bb.x + bb.width, bb.y + bb.height),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x + bb.width, bb.y + bb.height),
-                        Vector2::new(bb.x, bb.y + bb.height),
-                        COLOR_RED,
-                    );
-                    rl_draw_handle.draw_line_v(
-                        Vector2::new(bb.x, bb.y + bb.height),
-                        Vector2::new(bb.x, bb.y),
-                        COLOR_RED,
-                    );
-                }
-            }
+    fn import_svg(&mut self, svg: &str) -> Result<(), String> {
+        let path_data = if svg.contains("<path") {
+            svg_document_path_data(svg).ok_or("No path data in the SVG document")?
+        } else {
+            svg
+        };
+        // The spline holds a single sub path
+        let sub_path = parse_svg_path(path_data)?
+            .into_iter()
+            .next()
+            .ok_or("The SVG path is empty")?;
+        self.spline = Spline::from_cubics(&sub_path.cubics, sub_path.is_closed)
+            .ok_or("The SVG path is empty")?;
+        self.transform = None;
+        self.box_selection_start = None;
+        self.active_join_point = None;
+        Ok(())
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.transform.is_some() {
+            return false;
+        }
+        self.track_edit(false);
+        let current_state = self.history_state();
+        match self.history.undo(current_state) {
+            Some(state) => self.restore_state(state),
+            None => false,
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        if self.transform.is_some() {
+            return false;
+        }
+        self.track_edit(false);
+        let current_state = self.history_state();
+        match self.history.redo(current_state) {
+            Some(state) => self.restore_state(state),
+            None => false,
         }
     }
 }
-impl Default for BezierSplineScene {
-    fn default() -> Self {
-        let mut points: Vec<Rc<RefCell<Box<dyn MovableGuiPoint>>>> = vec![Rc::new(RefCell::new(
-            Box::new(JoinPoint::new(Vector2::new(300.0, 600.0), None, None)),
-        ))];
+impl BezierSplineScene {
+    fn to_save(&self) -> SplineSceneSave {
+        SplineSceneSave {
+            joins: self.spline.joins().iter().map(JoinSave::from).collect(),
+            is_closed_loop: self.spline.is_closed_loop(),
+            animated: self.animated,
+            debug_draw: self.debug_draw,
+            t: self.t,
+            draw_bounding_box: self.draw_bounding_box,
+            lock_move: self.lock_move,
+        }
+    }
 
-        points.push(Rc::new(RefCell::new(Box::new(ControlPoint::new(
-            Vector2::new(600.0, 300.0),
-            None,
-            Some(&points[0]),
-        )))));
+    fn apply_save(&mut self, save: SplineSceneSave) -> Result<(), String> {
+        self.spline = Spline::from_joins(
+            save.joins.iter().map(SplineJoin::from).collect(),
+            save.is_closed_loop,
+        )
+        .ok_or("A spline needs at least 2 join points")?;
+        self.animated = save.animated;
+        self.debug_draw = save.debug_draw;
+        self.t = save.t.clamp(0.0, 1.0);
+        self.draw_bounding_box = save.draw_bounding_box;
+        self.lock_move = save.lock_move;
+        self.animation_bounce = false;
+        self.transform = None;
+        self.box_selection_start = None;
+        self.active_join_point = None;
+        Ok(())
+    }
 
-        points.push(Rc::new(RefCell::new(Box::new(ControlPoint::new(
-            Vector2::new(900.0, 300.0),
-            None,
-            None,
-        )))));
+    /// State tracked by the undo history (the value of t is not an edit)
+    fn history_state(&self) -> SplineSceneSave {
+        SplineSceneSave {
+            t: 0.0,
+            ..self.to_save()
+        }
+    }
 
-        points.push(Rc::new(RefCell::new(Box::new(JoinPoint::new(
-            Vector2::new(1200.0, 600.0),
-            Some(&points[2]),
-            None,
-        )))));
-
-        points[0]
-            .borrow_mut()
-            .set_constraint(JoinPointConstraintID::NextControlPoint as usize, &points[1]);
-        points[2].borrow_mut().set_constraint(
-            ControlPointConstraintID::MirrorJoinPoint as usize,
-            &points[3],
+    /// Track the changes made since the last tracked state as a new edit (merged with the previous one when set)
+    /// Nothing is tracked while points are transformed so that a whole drag is a single edit. Edits made with the GUI
+    /// are tracked on the next update.
+    fn track_edit(&mut self, merge: bool) {
+        if self.transform.is_some() {
+            return;
+        }
+        self.history.track(self.history_state(), merge);
+    }
+
+    fn geometry(&self) -> SplineGeometry {
+        (
+            self.spline
+                .segments()
+                .map(|segment| segment.map(|p| p.position))
+                .collect(),
+            self.spline.is_closed_loop(),
+        )
+    }
+
+    /// Intersect, outline and measure the spline again when it, the line or the stroke style changed
+    fn update_computed_curves(&mut self) {
+        let geometry = self.geometry();
+        let is_edited = self.computed_geometry.as_ref() != Some(&geometry);
+        if is_edited {
+            self.self_intersections = self.spline.self_intersections();
+            self.arc_length_table = self.spline.arc_length_table();
+        }
+        let line = self.show_line.then(|| self.line());
+        if is_edited || line != self.intersected_line {
+            self.line_intersections = line
+                .map(|line| self.spline.line_intersections(line))
+                .unwrap_or_default();
+            self.intersected_line = line;
+        }
+        let outline_style = self.show_outline.then_some(self.stroke_style);
+        if is_edited || outline_style != self.outline_style {
+            self.outlines = outline_style
+                .map(|style| self.spline.stroke_outline(&style, OUTLINE_TOLERANCE))
+                .unwrap_or_default();
+            self.outlines
+                .sort_by(|a, b| enclosed_area(b).total_cmp(&enclosed_area(a)));
+            self.outline_style = outline_style;
+        }
+        self.computed_geometry = Some(geometry);
+    }
+
+    fn line(&self) -> Line {
+        Line::new(
+            self.line_points[0].position,
+            self.line_points[1].position,
+            self.line_kind,
+        )
+    }
+
+    fn selected_point_ids(&self) -> Vec<SplinePointId> {
+        self.spline
+            .point_ids()
+            .filter(|point_id| self.spline.point(*point_id).is_selected())
+            .collect()
+    }
+
+    /// Start transforming the selected points (nothing happens without selection)
+    fn start_transform(&mut self, kind: TransformKind, start: Vector2) {
+        let selection_center = centroid(
+            self.selected_point_ids()
+                .into_iter()
+                .map(|point_id| self.spline.point(point_id).position),
         );
+        if let Some(center) = selection_center {
+            self.transform = Some((
+                GroupTransform::new(kind, self.pivot.unwrap_or(center), start),
+                self.spline.clone(),
+            ));
+        }
+    }
+
+    /// Pivot to draw (custom pivot or pivot of the rotation or scale in progress)
+    fn transform_pivot(&self) -> Option<Vector2> {
+        match &self.transform {
+            Some((transform, _)) if transform.kind != TransformKind::Move => Some(transform.pivot),
+            _ => self.pivot,
+        }
+    }
+
+    fn restore_state(&mut self, state: SplineSceneSave) -> bool {
+        let t = self.t;
+        if self.apply_save(state).is_err() {
+            return false;
+        }
+        self.t = t;
+        true
+    }
+}
 
-        BezierSplineScene {
-            points,
+/// Area of the polygon of the join points of a closed spline
+fn enclosed_area(spline: &Spline) -> f32 {
+    let joins = spline.joins();
+    (0..joins.len())
+        .map(|i| {
+            let a = joins[i].join.position;
+            let b = joins[(i + 1) % joins.len()].join.position;
+            a.x * b.y - a.y * b.x
+        })
+        .sum::<f32>()
+        .abs()
+        * 0.5
+}
+
+impl Default for BezierSplineScene {
+    fn default() -> Self {
+        let spline = Spline::new([
+            Vector2::new(300.0, 600.0),
+            Vector2::new(600.0, 300.0),
+            Vector2::new(900.0, 300.0),
+            Vector2::new(1200.0, 600.0),
+        ]);
+        let arc_length_table = spline.arc_length_table();
+        let mut scene = BezierSplineScene {
+            spline,
             animated: true,
             animation_bounce: false,
-            has_point_selected: false,
             debug_draw: true,
             t: 0.5,
             draw_bounding_box: false,
             lock_move: true,
-            is_closed_loop: false,
-        }
+            active_join_point: None,
+            transform: None,
+            box_selection_start: None,
+            pivot: None,
+            hovered_curve_point: None,
+            show_line: false,
+            line_points: [
+                BasicPoint::new(Vector2::new(250.0, 450.0), COLOR_YELLOW),
+                BasicPoint::new(Vector2::new(1250.0, 450.0), COLOR_YELLOW),
+            ],
+            line_kind: LineKind::Line,
+            dragged_line_point: None,
+            line_intersections: Vec::new(),
+            intersected_line: None,
+            self_intersections: Vec::new(),
+            computed_geometry: None,
+            show_outline: false,
+            stroke_style: StrokeStyle {
+                width: 40.0,
+                join: StrokeJoin::Miter,
+                cap: StrokeCap::Butt,
+            },
+            outlines: Vec::new(),
+            outline_style: None,
+            arc_length_table,
+            camera: SceneCamera::default(),
+            grid: Grid::default(),
+            history: History::new(HISTORY_CAPACITY),
+        };
+        scene.track_edit(false);
+        scene
     }
 }
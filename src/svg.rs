@@ -0,0 +1,595 @@
+use raylib::prelude::*;
+
+use crate::colors::*;
+
+const SVG_MARGIN: f32 = 20.0;
+const SVG_STROKE_WIDTH: f32 = 3.0;
+
+/// Shortest decimal representation of a coordinate (at most 3 decimals)
+fn format_number(value: f32) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn format_point(point: Vector2) -> String {
+    format!("{} {}", format_number(point.x), format_number(point.y))
+}
+
+fn format_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// SVG path data of a chain of cubic Bezier
+/// A new sub path is started whenever a cubic does not start where the previous one ended.
+/// Closed chains are ended with `Z`.
+pub fn svg_path_data(cubics: &[[Vector2; 4]], is_closed: bool) -> String {
+    let mut commands = Vec::with_capacity(cubics.len() + 2);
+    let mut current_position: Option<Vector2> = None;
+    for cubic in cubics {
+        if current_position.is_none_or(|p| p.distance_to(cubic[0]) > 1e-3) {
+            commands.push(format!("M {}", format_point(cubic[0])));
+        }
+        commands.push(format!(
+            "C {} {} {}",
+            format_point(cubic[1]),
+            format_point(cubic[2]),
+            format_point(cubic[3])
+        ));
+        current_position = Some(cubic[3]);
+    }
+    if is_closed && !cubics.is_empty() {
+        commands.push("Z".to_string());
+    }
+    commands.join(" ")
+}
+
+/// Standalone SVG document drawing a chain of cubic Bezier
+/// The view box fits the control points, which always contain the curve.
+pub fn svg_document(cubics: &[[Vector2; 4]], is_closed: bool) -> String {
+    let (mut min, mut max) = (
+        Vector2::new(f32::INFINITY, f32::INFINITY),
+        Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    );
+    for point in cubics.iter().flatten() {
+        min = Vector2::new(min.x.min(point.x), min.y.min(point.y));
+        max = Vector2::new(max.x.max(point.x), max.y.max(point.y));
+    }
+    if cubics.is_empty() {
+        (min, max) = (Vector2::zero(), Vector2::zero());
+    }
+    let origin = min - Vector2::new(SVG_MARGIN, SVG_MARGIN);
+    let size = max - min + Vector2::new(SVG_MARGIN, SVG_MARGIN) * 2.0;
+
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {}\" width=\"{}\" height=\"{}\">\n",
+            "  <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+            "</svg>\n"
+        ),
+        format_point(origin),
+        format_point(size),
+        format_number(size.x),
+        format_number(size.y),
+        svg_path_data(cubics, is_closed),
+        format_color(COLOR_GREEN),
+        format_number(SVG_STROKE_WIDTH),
+    )
+}
+
+/// One sub path of an SVG path, converted to cubic Bezier
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgSubPath {
+    pub cubics: Vec<[Vector2; 4]>,
+    pub is_closed: bool,
+}
+
+/// Cursor over SVG path data
+struct PathDataReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+impl<'a> PathDataReader<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.position >= self.data.len()
+    }
+
+    /// Next command letter (if the next token is one)
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.data.get(self.position)?;
+        if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+            self.position += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |reader: &mut Self| {
+            let digits_start = reader.position;
+            while reader
+                .data
+                .get(reader.position)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                reader.position += 1;
+            }
+            reader.position > digits_start
+        };
+        if matches!(self.data.get(self.position), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let mut has_digits = digits(self);
+        if self.data.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.position = start;
+            return Err(format!("Expected a number at position {}", start));
+        }
+        if matches!(self.data.get(self.position), Some(b'e' | b'E')) {
+            let exponent_start = self.position;
+            self.position += 1;
+            if matches!(self.data.get(self.position), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = exponent_start;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or_else(|| format!("Invalid number at position {}", start))
+    }
+
+    fn point(&mut self) -> Result<Vector2, String> {
+        Ok(Vector2::new(self.number()?, self.number()?))
+    }
+
+    /// Arc flags are a single 0 or 1 and may not be separated from the next value
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(format!("Expected a flag at position {}", self.position)),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+fn line_to_cubic(from: Vector2, to: Vector2) -> [Vector2; 4] {
+    [from, from.lerp(to, 1.0 / 3.0), from.lerp(to, 2.0 / 3.0), to]
+}
+
+fn quadratic_to_cubic(from: Vector2, control: Vector2, to: Vector2) -> [Vector2; 4] {
+    [
+        from,
+        from + (control - from) * (2.0 / 3.0),
+        to + (control - to) * (2.0 / 3.0),
+        to,
+    ]
+}
+
+/// Signed angle from u to v
+fn angle_between(u: Vector2, v: Vector2) -> f32 {
+    (u.x * v.y - u.y * v.x).atan2(u.dot(v))
+}
+
+/// Convert an elliptical arc (SVG endpoint parametrization) to cubic Bezier of at most 90° each
+fn arc_to_cubics(
+    from: Vector2,
+    radius: Vector2,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Vector2,
+) -> Vec<[Vector2; 4]> {
+    if from.distance_to(to) < f32::EPSILON {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (radius.x.abs(), radius.y.abs());
+    if rx < f32::EPSILON || ry < f32::EPSILON {
+        return vec![line_to_cubic(from, to)];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let rotate = |v: Vector2| Vector2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y);
+
+    // Center parametrization (SVG implementation notes, F.6.5)
+    let half_difference = (from - to) * 0.5;
+    let p = Vector2::new(
+        cos * half_difference.x + sin * half_difference.y,
+        -sin * half_difference.x + cos * half_difference.y,
+    );
+    let lambda = (p.x * p.x) / (rx * rx) + (p.y * p.y) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * p.y * p.y - ry * ry * p.x * p.x;
+    let denominator = rx * rx * p.y * p.y + ry * ry * p.x * p.x;
+    let coefficient =
+        (numerator / denominator).max(0.0).sqrt() * if large_arc == sweep { -1.0 } else { 1.0 };
+    let center_prime = Vector2::new(coefficient * rx * p.y / ry, -coefficient * ry * p.x / rx);
+    let center = rotate(center_prime) + (from + to) * 0.5;
+
+    let start_vector = Vector2::new((p.x - center_prime.x) / rx, (p.y - center_prime.y) / ry);
+    let end_vector = Vector2::new((-p.x - center_prime.x) / rx, (-p.y - center_prime.y) / ry);
+    let start_angle = angle_between(Vector2::new(1.0, 0.0), start_vector);
+    let mut sweep_angle = angle_between(start_vector, end_vector);
+    if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= std::f32::consts::TAU;
+    } else if sweep && sweep_angle < 0.0 {
+        sweep_angle += std::f32::consts::TAU;
+    }
+
+    let segment_count = (sweep_angle.abs() / std::f32::consts::FRAC_PI_2)
+        .ceil()
+        .max(1.0) as usize;
+    let delta = sweep_angle / segment_count as f32;
+    let k = 4.0 / 3.0 * (delta / 4.0).tan();
+    let to_ellipse = |v: Vector2| center + rotate(Vector2::new(v.x * rx, v.y * ry));
+    let mut cubics = (0..segment_count)
+        .map(|i| {
+            let (sin0, cos0) = (start_angle + delta * i as f32).sin_cos();
+            let (sin1, cos1) = (start_angle + delta * (i + 1) as f32).sin_cos();
+            [
+                to_ellipse(Vector2::new(cos0, sin0)),
+                to_ellipse(Vector2::new(cos0 - k * sin0, sin0 + k * cos0)),
+                to_ellipse(Vector2::new(cos1 + k * sin1, sin1 - k * cos1)),
+                to_ellipse(Vector2::new(cos1, sin1)),
+            ]
+        })
+        .collect::<Vec<_>>();
+    // Snap the ends to remove rounding errors
+    cubics[0][0] = from;
+    cubics[segment_count - 1][3] = to;
+    cubics
+}
+
+/// Parse SVG path data (M, L, H, V, C, S, Q, T, A and Z, absolute and relative) into cubic Bezier sub paths
+pub fn parse_svg_path(data: &str) -> Result<Vec<SvgSubPath>, String> {
+    let mut reader = PathDataReader::new(data);
+    let mut sub_paths = Vec::new();
+    let mut cubics: Vec<[Vector2; 4]> = Vec::new();
+    let mut current = Vector2::zero();
+    let mut start = Vector2::zero();
+    // Control point reflected by S (cubic) and T (quadratic)
+    let mut last_cubic_control: Option<Vector2> = None;
+    let mut last_quadratic_control: Option<Vector2> = None;
+    let mut command: Option<u8> = None;
+
+    while !reader.is_at_end() {
+        let c = match reader.command() {
+            Some(c) => c,
+            // Implicit repetition of the previous command (M is repeated as L)
+            None => match command {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(b'Z' | b'z') | None => {
+                    return Err(format!(
+                        "Expected a command at position {}",
+                        reader.position
+                    ))
+                }
+                Some(c) => c,
+            },
+        };
+        if command.is_none() && !c.eq_ignore_ascii_case(&b'M') {
+            return Err("A path must start with a move to command".to_string());
+        }
+        command = Some(c);
+        let relative = c.is_ascii_lowercase();
+        let offset = if relative { current } else { Vector2::zero() };
+        let mut next_cubic_control = None;
+        let mut next_quadratic_control = None;
+
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                if !cubics.is_empty() {
+                    sub_paths.push(SvgSubPath {
+                        cubics: std::mem::take(&mut cubics),
+                        is_closed: false,
+                    });
+                }
+                current = reader.point()? + offset;
+                start = current;
+            }
+            b'L' => {
+                let to = reader.point()? + offset;
+                cubics.push(line_to_cubic(current, to));
+                current = to;
+            }
+            b'H' => {
+                let to = Vector2::new(reader.number()? + offset.x, current.y);
+                cubics.push(line_to_cubic(current, to));
+                current = to;
+            }
+            b'V' => {
+                let to = Vector2::new(current.x, reader.number()? + offset.y);
+                cubics.push(line_to_cubic(current, to));
+                current = to;
+            }
+            b'C' | b'S' => {
+                let first_control = if c.eq_ignore_ascii_case(&b'C') {
+                    reader.point()? + offset
+                } else {
+                    last_cubic_control.map_or(current, |control| current * 2.0 - control)
+                };
+                let second_control = reader.point()? + offset;
+                let to = reader.point()? + offset;
+                cubics.push([current, first_control, second_control, to]);
+                next_cubic_control = Some(second_control);
+                current = to;
+            }
+            b'Q' | b'T' => {
+                let control = if c.eq_ignore_ascii_case(&b'Q') {
+                    reader.point()? + offset
+                } else {
+                    last_quadratic_control.map_or(current, |control| current * 2.0 - control)
+                };
+                let to = reader.point()? + offset;
+                cubics.push(quadratic_to_cubic(current, control, to));
+                next_quadratic_control = Some(control);
+                current = to;
+            }
+            b'A' => {
+                let radius = reader.point()?;
+                let rotation = reader.number()?;
+                let large_arc = reader.flag()?;
+                let sweep = reader.flag()?;
+                let to = reader.point()? + offset;
+                cubics.extend(arc_to_cubics(
+                    current, radius, rotation, large_arc, sweep, to,
+                ));
+                current = to;
+            }
+            b'Z' => {
+                if current.distance_to(start) > 1e-3 {
+                    cubics.push(line_to_cubic(current, start));
+                }
+                if !cubics.is_empty() {
+                    sub_paths.push(SvgSubPath {
+                        cubics: std::mem::take(&mut cubics),
+                        is_closed: true,
+                    });
+                }
+                current = start;
+            }
+            _ => return Err(format!("Unknown path command '{}'", c as char)),
+        }
+        last_cubic_control = next_cubic_control;
+        last_quadratic_control = next_quadratic_control;
+    }
+    if !cubics.is_empty() {
+        sub_paths.push(SvgSubPath {
+            cubics,
+            is_closed: false,
+        });
+    }
+    Ok(sub_paths)
+}
+
+/// Path data of the first `<path>` element of an SVG document
+pub fn svg_document_path_data(document: &str) -> Option<&str> {
+    let tag_start = document.find("<path")?;
+    let tag = &document[tag_start..];
+    let tag = &tag[..tag.find('>')?];
+    let bytes = tag.as_bytes();
+    // The attribute must be exactly "d" (not "id" for example)
+    let attribute = tag
+        .match_indices("d=")
+        .map(|(i, _)| i)
+        .find(|i| *i > 0 && bytes[i - 1].is_ascii_whitespace())?;
+    let value = &tag[attribute + 2..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(&value[..value.find(quote)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bezier::evalute_bezier_curve;
+
+    #[test]
+    fn numbers_are_trimmed() {
+        assert_eq!(format_number(12.0), "12");
+        assert_eq!(format_number(0.5), "0.5");
+        assert_eq!(format_number(-1.25), "-1.25");
+        assert_eq!(format_number(-0.0001), "0");
+        assert_eq!(format_number(1.0 / 3.0), "0.333");
+    }
+
+    #[test]
+    fn closed_chain_path_data() {
+        let a = Vector2::new(0.0, 0.0);
+        let b = Vector2::new(100.0, 0.0);
+        let c = Vector2::new(50.0, 80.0);
+        let cubics = [
+            [a, Vector2::new(30.0, -10.0), Vector2::new(70.0, -10.0), b],
+            [b, Vector2::new(100.0, 40.0), Vector2::new(80.0, 80.0), c],
+            [c, Vector2::new(20.0, 80.0), Vector2::new(0.0, 40.0), a],
+        ];
+        assert_eq!(
+            svg_path_data(&cubics, true),
+            "M 0 0 C 30 -10 70 -10 100 0 C 100 40 80 80 50 80 C 20 80 0 40 0 0 Z"
+        );
+        assert_eq!(
+            svg_path_data(&cubics[..1], false),
+            "M 0 0 C 30 -10 70 -10 100 0"
+        );
+    }
+
+    #[test]
+    fn document_view_box_fits_points() {
+        let cubics = [[
+            Vector2::new(10.0, 20.0),
+            Vector2::new(60.0, 0.0),
+            Vector2::new(110.0, 50.0),
+            Vector2::new(210.0, 120.0),
+        ]];
+        let document = svg_document(&cubics, false);
+        assert!(document.starts_with("<?xml"));
+        assert!(document.contains("viewBox=\"-10 -20 240 160\""));
+        assert!(document.contains("d=\"M 10 20 C 60 0 110 50 210 120\""));
+        assert!(document.trim_end().ends_with("</svg>"));
+    }
+
+    fn assert_close(a: Vector2, b: Vector2) {
+        assert!(a.distance_to(b) < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn parse_absolute_and_relative_lines() {
+        let sub_paths = parse_svg_path("M10,10 L20 10 h10 v-5 H0 V0 l5 5 20,0").unwrap();
+        assert_eq!(sub_paths.len(), 1);
+        let ends = sub_paths[0].cubics.iter().map(|c| c[3]).collect::<Vec<_>>();
+        let expected = [
+            (20.0, 10.0),
+            (30.0, 10.0),
+            (30.0, 5.0),
+            (0.0, 5.0),
+            (0.0, 0.0),
+            (5.0, 5.0),
+            (25.0, 5.0),
+        ];
+        assert_eq!(ends.len(), expected.len());
+        for (end, (x, y)) in ends.iter().zip(expected) {
+            assert_close(*end, Vector2::new(x, y));
+        }
+        assert!(!sub_paths[0].is_closed);
+        // Lines are straight cubic Bezier
+        assert_close(
+            sub_paths[0].cubics[0][1],
+            Vector2::new(10.0 + 10.0 / 3.0, 10.0),
+        );
+    }
+
+    #[test]
+    fn parse_smooth_curves_reflect_controls() {
+        let sub_paths = parse_svg_path("M0 0 C 10 -10 20 -10 30 0 s 20 10 30 0").unwrap();
+        let cubics = &sub_paths[0].cubics;
+        assert_eq!(cubics.len(), 2);
+        assert_close(cubics[1][1], Vector2::new(40.0, 10.0));
+        assert_close(cubics[1][2], Vector2::new(50.0, 10.0));
+        assert_close(cubics[1][3], Vector2::new(60.0, 0.0));
+
+        let sub_paths = parse_svg_path("M0 0 Q 10 20 20 0 T 40 0").unwrap();
+        let cubics = &sub_paths[0].cubics;
+        // Reflected quadratic control is (30, -20)
+        assert_close(cubics[1][1], Vector2::new(20.0 + 20.0 / 3.0, -40.0 / 3.0));
+        assert_close(cubics[1][2], Vector2::new(40.0 - 20.0 / 3.0, -40.0 / 3.0));
+        let quadratic = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 20.0),
+            Vector2::new(20.0, 0.0),
+        ];
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert_close(
+                evalute_bezier_curve(&quadratic, t),
+                evalute_bezier_curve(&cubics[0], t),
+            );
+        }
+    }
+
+    #[test]
+    fn parse_arcs_as_cubics() {
+        // Full circle of radius 50 centered on (100, 100) made of two half circles
+        let sub_paths = parse_svg_path("M50 100 a50 50 0 1 0 100 0 A50,50 0 1,0 50,100 z").unwrap();
+        assert_eq!(sub_paths.len(), 1);
+        assert!(sub_paths[0].is_closed);
+        assert_eq!(sub_paths[0].cubics.len(), 4);
+        let center = Vector2::new(100.0, 100.0);
+        for cubic in sub_paths[0].cubics.iter() {
+            for i in 0..=10 {
+                let distance = evalute_bezier_curve(cubic, i as f32 / 10.0).distance_to(center);
+                assert!((distance - 50.0).abs() < 0.05);
+            }
+        }
+        // Counter clockwise in screen space goes through the bottom first
+        assert!(evalute_bezier_curve(&sub_paths[0].cubics[0], 1.0).y > 100.0);
+        // Flags may be packed with the next value
+        let packed = parse_svg_path("M0 0a10 10 0 0150 0").unwrap();
+        assert_close(packed[0].cubics.last().unwrap()[3], Vector2::new(50.0, 0.0));
+    }
+
+    #[test]
+    fn parse_closed_and_multiple_sub_paths() {
+        let sub_paths = parse_svg_path("M0 0 L10 0 L10 10 Z m20 0 l10 0").unwrap();
+        assert_eq!(sub_paths.len(), 2);
+        assert!(sub_paths[0].is_closed);
+        // Closing line added by Z
+        assert_eq!(sub_paths[0].cubics.len(), 3);
+        assert_close(sub_paths[0].cubics[2][3], Vector2::zero());
+        assert!(!sub_paths[1].is_closed);
+        assert_close(sub_paths[1].cubics[0][0], Vector2::new(20.0, 0.0));
+
+        assert!(parse_svg_path("L 10 10").is_err());
+        assert!(parse_svg_path("M 0 0 L 10").is_err());
+        assert!(parse_svg_path("M 0 0 X 10 10").is_err());
+    }
+
+    #[test]
+    fn export_then_parse_round_trip() {
+        let cubics = [
+            [
+                Vector2::new(0.0, 0.0),
+                Vector2::new(30.5, -10.0),
+                Vector2::new(70.0, -10.25),
+                Vector2::new(100.0, 0.0),
+            ],
+            [
+                Vector2::new(100.0, 0.0),
+                Vector2::new(130.0, 10.0),
+                Vector2::new(0.0, 40.0),
+                Vector2::new(0.0, 0.0),
+            ],
+        ];
+        let document = svg_document(&cubics, true);
+        let data = svg_document_path_data(&document).unwrap();
+        let sub_paths = parse_svg_path(data).unwrap();
+        assert_eq!(
+            sub_paths,
+            vec![SvgSubPath {
+                cubics: cubics.to_vec(),
+                is_closed: true
+            }]
+        );
+        assert_eq!(
+            svg_document_path_data(r#"<svg><path id="a" d='M 0 0 L 1 1'/></svg>"#),
+            Some("M 0 0 L 1 1")
+        );
+    }
+}